@@ -0,0 +1,23 @@
+//! Helpers shared by the benches. Each bench installs `CountingAllocator` itself, so that memory is
+//! reported alongside timings.
+
+use std::time::{Duration, Instant};
+
+use advent_of_rust_22::alloc_stats::{Measurement, MemoryUsage};
+
+/**
+Runs `f` once, printing `label`, the elapsed time and (if `CountingAllocator` is installed) memory
+usage. Returns the result of `f` with the elapsed time.
+ */
+pub fn measure<T>(label: &str, f: impl FnOnce() -> T) -> (T, Duration) {
+    let measurement = Measurement::start();
+    let bgn = Instant::now();
+    let result = f();
+    let elapsed = bgn.elapsed();
+    let memory: Option<MemoryUsage> = measurement.map(Measurement::finish);
+    match memory {
+        Some(memory) => println!("{:<20} {:>12?}  {}", label, elapsed, memory),
+        None => println!("{:<20} {:>12?}", label, elapsed),
+    }
+    (result, elapsed)
+}
@@ -0,0 +1,122 @@
+//! Compares `Day3`'s `ItemSet` bitsets against the previous `HashSet<&u8>` approach on generated
+//! inputs. Run with `cargo bench --bench day3`; set `DAY3_BENCH_RUCKSACKS` to change the input size.
+
+mod common;
+
+use std::collections::HashSet;
+
+use advent_of_rust_22::alloc_stats::CountingAllocator;
+use advent_of_rust_22::get_solutions::day3::Day3;
+use advent_of_rust_22::get_solutions::AOCSolutions;
+
+use common::measure;
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const DEFAULT_RUCKSACKS: usize = 3_000_000;
+const HALF_LEN: usize = 12;
+
+/// xorshift64, good enough for generating inputs.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+/**
+Generates `groups` groups of three rucksacks such that each rucksack has exactly one item common to
+both compartments and each group has exactly one item (the badge) common to all three rucksacks.
+ */
+fn generate_input(groups: usize, rng: &mut Rng) -> String {
+    let mut letters: Vec<u8> = (b'a'..=b'z').chain(b'A'..=b'Z').collect();
+    let mut input = String::with_capacity(groups * 3 * (2 * HALF_LEN + 1));
+    for _ in 0..groups {
+        for i in (1..letters.len()).rev() {
+            letters.swap(i, rng.below(i + 1));
+        }
+        let badge = letters[0];
+        let pools: Vec<&[u8]> = letters[1..49].chunks(8).collect();
+        for member in 0..3 {
+            let (pool_1, pool_2) = (pools[2 * member], pools[2 * member + 1]);
+            let mut half_1: Vec<u8> = (0..HALF_LEN).map(|_| pool_1[rng.below(pool_1.len())]).collect();
+            let mut half_2: Vec<u8> = (0..HALF_LEN).map(|_| pool_2[rng.below(pool_2.len())]).collect();
+            let shared = pool_1[0];
+            half_1[rng.below(HALF_LEN)] = shared;
+            half_2[rng.below(HALF_LEN)] = shared;
+            // Keep `shared` present if the badge lands on its position
+            let badge_pos = (half_1.iter().position(|u| *u == shared).unwrap() + 1) % HALF_LEN;
+            half_1[badge_pos] = badge;
+            input.push_str(std::str::from_utf8(&half_1).unwrap());
+            input.push_str(std::str::from_utf8(&half_2).unwrap());
+            input.push('\n');
+        }
+    }
+    input
+}
+
+fn priority(u: u8) -> i64 {
+    if u.is_ascii_uppercase() { (u - b'A' + 27).into() } else { (u - b'a' + 1).into() }
+}
+
+fn hashset_star_1(input: &str) -> i64 {
+    input.lines()
+        .map(|l| {
+            let l = l.trim().as_bytes();
+            let (c1, c2): (HashSet<&u8>, HashSet<&u8>) = (
+                l[..l.len() / 2].iter().collect(),
+                l[l.len() / 2..].iter().collect(),
+            );
+            c1.intersection(&c2).map(|u| priority(**u)).sum::<i64>()
+        })
+        .sum()
+}
+
+fn hashset_star_2(input: &str) -> i64 {
+    input.lines().collect::<Vec<&str>>()
+        .chunks_exact(3)
+        .map(|group| {
+            group.iter()
+                .map(|l| l.trim().as_bytes().iter().collect::<HashSet<&u8>>())
+                .reduce(|acc, rhs| acc.intersection(&rhs).cloned().collect())
+                .unwrap()
+                .iter()
+                .map(|u| priority(**u))
+                .sum::<i64>()
+        })
+        .sum()
+}
+
+fn main() {
+    let rucksacks: usize = std::env::var("DAY3_BENCH_RUCKSACKS").ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_RUCKSACKS);
+    let threads = std::thread::available_parallelism().map_or(1, usize::from);
+    let input = generate_input(rucksacks.div_ceil(3), &mut Rng(0x2022_1203));
+    println!("Day3: {} rucksacks, {} bytes", rucksacks.div_ceil(3) * 3, input.len());
+
+    let (expected, hashset_time) = measure("hashset star 1", || hashset_star_1(&input));
+    let (actual, bitset_time) = measure("bitset star 1", || Day3::get_star_1(&input).unwrap());
+    assert_eq!(expected, actual);
+    println!("star 1 speedup: {:.1}x", hashset_time.as_secs_f64() / bitset_time.as_secs_f64());
+    let (actual, parallel_time) = measure("parallel star 1", || Day3::get_star_1_parallel(&input, threads).unwrap());
+    assert_eq!(expected, actual);
+    println!("star 1 speedup on {} threads: {:.1}x", threads, hashset_time.as_secs_f64() / parallel_time.as_secs_f64());
+
+    let (expected, hashset_time) = measure("hashset star 2", || hashset_star_2(&input));
+    let (actual, bitset_time) = measure("bitset star 2", || Day3::get_star_2(&input).unwrap());
+    assert_eq!(expected, actual);
+    println!("star 2 speedup: {:.1}x", hashset_time.as_secs_f64() / bitset_time.as_secs_f64());
+    let (actual, parallel_time) = measure("parallel star 2", || Day3::get_star_2_parallel(&input, threads).unwrap());
+    assert_eq!(expected, actual);
+    println!("star 2 speedup on {} threads: {:.1}x", threads, hashset_time.as_secs_f64() / parallel_time.as_secs_f64());
+}
@@ -0,0 +1,41 @@
+//! Compares `Day6::find_marker` against the thread-pooled `Day6::pooled_decode` on generated
+//! datastreams. Run with `cargo bench --bench day6`; set `DAY6_BENCH_LEN` to change the stream length.
+
+mod common;
+
+use advent_of_rust_22::alloc_stats::CountingAllocator;
+use advent_of_rust_22::get_solutions::day6::Day6;
+
+use common::measure;
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const DEFAULT_LEN: usize = 200_000;
+const POOL_THREADS: usize = 8;
+
+/**
+Generates a stream of `len` bytes whose only windows of 4 or 14 distinct bytes are at its very end,
+so both detectors have to scan all of it.
+ */
+fn generate_stream(len: usize) -> Vec<u8> {
+    // Cycling through 3 letters never yields 4 distinct consecutive bytes
+    let mut stream: Vec<u8> = (0..len.saturating_sub(14)).map(|idx| b'a' + (idx % 3) as u8).collect();
+    stream.extend(b"nopqrstuvwxyzA");
+    stream
+}
+
+fn main() {
+    let len: usize = std::env::var("DAY6_BENCH_LEN").ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_LEN);
+    let stream = generate_stream(len);
+    println!("Day6: {} bytes", stream.len());
+
+    for window in [4, 14] {
+        let (expected, pooled_time) = measure(&format!("pooled window {}", window), || Day6::pooled_decode(&stream, window, POOL_THREADS));
+        let (actual, window_time) = measure(&format!("sliding window {}", window), || Day6::find_marker(&stream, window));
+        assert_eq!(expected, actual);
+        println!("window {} speedup: {:.0}x", window, pooled_time.as_secs_f64() / window_time.as_secs_f64());
+    }
+}
@@ -0,0 +1,165 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Maximum number of measurements in progress at once, see `Measurement::start`.
+pub const MAX_MEASUREMENTS: usize = 64;
+
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static TOTAL: AtomicUsize = AtomicUsize::new(0);
+/// Number of slots in use, so that allocations skip the slots while nothing is measured.
+static ACTIVE: AtomicUsize = AtomicUsize::new(0);
+static SLOTS: [PeakSlot; MAX_MEASUREMENTS] = [const { PeakSlot::new() }; MAX_MEASUREMENTS];
+
+/**
+Peak of live bytes tracked for one in-progress `Measurement`, so that starting a measurement never
+resets the peak of another.
+ */
+struct PeakSlot {
+    in_use: AtomicBool,
+    peak: AtomicUsize,
+}
+
+impl PeakSlot {
+    const fn new() -> PeakSlot {
+        PeakSlot { in_use: AtomicBool::new(false), peak: AtomicUsize::new(0) }
+    }
+}
+
+/**
+`System` allocator wrapper counting live, peak and total allocated bytes across the whole process.
+
+Only takes effect once installed by the binary with `#[global_allocator]` (see the `alloc-stats`
+feature). Counters are process-wide, so measurements of concurrently running solvers include each
+other's allocations, but each measurement keeps its own peak.
+ */
+pub struct CountingAllocator;
+
+fn record_alloc(size: usize) {
+    if !INSTALLED.load(Ordering::Relaxed) {
+        INSTALLED.store(true, Ordering::Relaxed);
+    }
+    TOTAL.fetch_add(size, Ordering::Relaxed);
+    let current = CURRENT.fetch_add(size, Ordering::Relaxed) + size;
+    if ACTIVE.load(Ordering::Relaxed) > 0 {
+        for slot in SLOTS.iter().filter(|slot| slot.in_use.load(Ordering::Relaxed)) {
+            slot.peak.fetch_max(current, Ordering::Relaxed);
+        }
+    }
+}
+
+fn record_dealloc(size: usize) {
+    CURRENT.fetch_sub(size, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() { record_alloc(layout.size()); }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc_zeroed(layout) };
+        if !ptr.is_null() { record_alloc(layout.size()); }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        record_dealloc(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = unsafe { System.realloc(ptr, layout, new_size) };
+        if !new_ptr.is_null() {
+            if new_size > layout.size() {
+                record_alloc(new_size - layout.size());
+            } else {
+                record_dealloc(layout.size() - new_size);
+            }
+        }
+        new_ptr
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// Highest number of live bytes above the baseline at the start of the measurement.
+    pub peak_bytes: usize,
+    /// Sum of all bytes allocated during the measurement, including growth by `realloc`.
+    pub total_bytes: usize,
+}
+
+impl fmt::Display for MemoryUsage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "peak {}, total {}", ByteSize(self.peak_bytes), ByteSize(self.total_bytes))
+    }
+}
+
+struct ByteSize(usize);
+
+impl fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+        let mut size = self.0 as f64;
+        let mut unit = 0;
+        while size >= 1024.0 && unit < UNITS.len() - 1 {
+            size /= 1024.0;
+            unit += 1;
+        }
+        if unit == 0 { write!(f, "{} B", self.0) } else { write!(f, "{:.1} {}", size, UNITS[unit]) }
+    }
+}
+
+/**
+In-progress measurement started by `Measurement::start`.
+ */
+pub struct Measurement {
+    slot: &'static PeakSlot,
+    base_current: usize,
+    base_total: usize,
+}
+
+impl Measurement {
+    /**
+    Starts measuring from the current live byte count. Returns `None` if `CountingAllocator` is
+    not the global allocator, or if `MAX_MEASUREMENTS` measurements are already in progress.
+     */
+    pub fn start() -> Option<Measurement> {
+        if !INSTALLED.load(Ordering::Relaxed) { return None; }
+        let slot = SLOTS.iter().find(|slot| {
+            slot.in_use.compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed).is_ok()
+        })?;
+        let base_current = CURRENT.load(Ordering::Relaxed);
+        slot.peak.store(base_current, Ordering::Relaxed);
+        ACTIVE.fetch_add(1, Ordering::Relaxed);
+        Some(Measurement { slot, base_current, base_total: TOTAL.load(Ordering::Relaxed) })
+    }
+
+    pub fn finish(self) -> MemoryUsage {
+        MemoryUsage {
+            peak_bytes: self.slot.peak.load(Ordering::Relaxed).saturating_sub(self.base_current),
+            total_bytes: TOTAL.load(Ordering::Relaxed) - self.base_total,
+        }
+    }
+}
+
+impl Drop for Measurement {
+    fn drop(&mut self) {
+        ACTIVE.fetch_sub(1, Ordering::Relaxed);
+        self.slot.in_use.store(false, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MemoryUsage;
+
+    #[test]
+    fn test_display() {
+        let usage = MemoryUsage { peak_bytes: 512, total_bytes: 3 << 20 };
+        assert_eq!(usage.to_string(), "peak 512 B, total 3.0 MiB");
+    }
+}
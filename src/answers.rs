@@ -0,0 +1,80 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+/**
+Known-correct answers of a day, keyed by star, as recorded in an answers file:
+
+```text
+# Comments and blank lines are ignored
+1: 24000
+2: 45000
+```
+
+A star may be left out while its answer is still unknown.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Answers {
+    stars: BTreeMap<usize, i64>,
+}
+
+impl Answers {
+    pub fn parse(text: &str) -> Result<Answers, String> {
+        let mut stars = BTreeMap::new();
+        for (idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') { continue; }
+
+            let parsed = line.split_once(':')
+                .and_then(|(star, answer)| Some((star.trim().parse::<usize>().ok()?, answer.trim().parse::<i64>().ok()?)));
+            match parsed {
+                Some((star @ (1 | 2), answer)) => {
+                    if stars.insert(star, answer).is_some() {
+                        return Err(format!("[answers::Answers::parse] Line {}: star {} recorded twice", idx + 1, star));
+                    }
+                },
+                _ => return Err(format!("[answers::Answers::parse] Line {}: expected `<1|2>: <answer>`, found \"{}\"", idx + 1, line)),
+            }
+        }
+        Ok(Answers { stars })
+    }
+
+    /**
+    Reads the answers file at `path`. A missing file means no answers are recorded yet.
+     */
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Answers, Error> {
+        match fs::read_to_string(path) {
+            Ok(text) => Answers::parse(&text).map_err(|msg| Error::new(ErrorKind::InvalidData, msg)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Answers::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn get(&self, star: usize) -> Option<i64> {
+        self.stars.get(&star).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let answers = Answers::parse("# day 1\n1: 24000\n\n  2 :-45000  \n").unwrap();
+        assert_eq!(answers.get(1), Some(24000));
+        assert_eq!(answers.get(2), Some(-45000));
+        assert_eq!(Answers::parse("2: 7").unwrap().get(1), None);
+        assert!(Answers::parse("1: 1\n1: 2").is_err());
+        assert!(Answers::parse("3: 1").is_err());
+        assert!(Answers::parse("1 24000").is_err());
+        assert!(Answers::parse("1: abc").is_err());
+    }
+
+    #[test]
+    fn test_load_missing_file() {
+        assert_eq!(Answers::load("does/not/exist").unwrap(), Answers::default());
+        assert_eq!(Answers::load("Cargo.toml").unwrap_err().kind(), ErrorKind::InvalidData);
+    }
+}
@@ -1,75 +1,218 @@
-use std::io::BufRead;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt;
+use std::io::{self, BufRead};
 
 use super::AOCSolutions;
 
-pub struct Day1; 
+/**
+Items carried by a single elf, as calories per item.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Elf {
+    items: Vec<i64>, 
+}
 
-impl AOCSolutions for Day1 {
-    fn get_star_1(input: &str) -> Result<i64, ()> {
-        let mut input = input.as_bytes(); 
-        let mut max: i64 = 0; 
-        let mut curr: i64 = 0; 
-        loop {
-            let mut buf = String::new(); 
-            let result = input.read_line(&mut buf)
-                .expect("[day1::get_star_1] Error while reading line from input"); 
-
-            match buf.as_str() {
-                "\n" | "" => {
-                    max = if curr >= max { curr } else { max };
-                    curr = 0;  
+impl Elf {
+    pub fn items(&self) -> &[i64] {
+        &self.items
+    }
+
+    /// Sum of all items, or `None` if it leaves the `i64` range.
+    pub fn total(&self) -> Option<i64> {
+        self.items.iter().try_fold(0i64, |acc, item| acc.checked_add(*item))
+    }
+
+    pub fn count(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        if self.items.is_empty() { return None; }
+        Some(self.total()? as f64 / self.items.len() as f64)
+    }
+
+    pub fn median(&self) -> Option<f64> {
+        if self.items.is_empty() { return None; }
+        let mut sorted = self.items.clone(); 
+        sorted.sort_unstable(); 
+        let mid = sorted.len() / 2; 
+        if sorted.len().is_multiple_of(2) {
+            // Summed as `i128`, the `i64` sum of two items may overflow
+            Some((sorted[mid - 1] as i128 + sorted[mid] as i128) as f64 / 2.0)
+        } else {
+            Some(sorted[mid] as f64)
+        }
+    }
+}
+
+/**
+Parsed puzzle input: one `Elf` per blank-line-separated group of integer lines.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inventory {
+    elves: Vec<Elf>, 
+}
+
+impl Inventory {
+    /**
+    Parses `input` into an `Inventory`, ignoring surrounding whitespace on each line and empty groups 
+    (e.g., from repeated blank lines). Returns `None` if any non-blank line is not an integer, or if 
+    the sum of a group leaves the `i64` range (like `stream_top_k`). 
+     */
+    pub fn parse(input: &str) -> Option<Inventory> {
+        let mut elves: Vec<Elf> = Vec::new(); 
+        let mut items: Vec<i64> = Vec::new(); 
+        let mut group_sum: i64 = 0; 
+        for line in input.lines().map(str::trim) {
+            if line.is_empty() {
+                if !items.is_empty() { elves.push(Elf { items: std::mem::take(&mut items) }); }
+                group_sum = 0; 
+                continue; 
+            }
+            match line.parse::<i64>() {
+                Ok(item) => {
+                    group_sum = match group_sum.checked_add(item) {
+                        Some(sum) => sum, 
+                        None => {
+                            eprintln!("[Day1::Inventory::parse] Group sum overflows `i64` at line: {}", line); 
+                            return None; 
+                        }, 
+                    }; 
+                    items.push(item); 
+                }, 
+                Err(_) => {
+                    eprintln!("[Day1::Inventory::parse] Input contains non-integer line: {}", line); 
+                    return None; 
                 }, 
-                _ => {
-                    curr += buf.trim().parse::<i64>()
-                        .expect(format!("[day1::get_star_1] Input contains non-integer lines: {}", buf).as_str()); 
-                }
             }
+        }
+        if !items.is_empty() { elves.push(Elf { items }); }
+        Some(Inventory { elves })
+    }
 
-            if result == 0 {
-                break;
-            }
+    pub fn elves(&self) -> &[Elf] {
+        &self.elves
+    }
+
+    /**
+    Returns the `n` largest per-elf totals in descending order (fewer if there are less than `n` elves). 
+
+    Keeps a min-heap of at most `n` totals, i.e., O(e log n) time and O(min(n, e)) extra space for e 
+    elves. 
+     */
+    pub fn top_n(&self, n: usize) -> Vec<i64> {
+        let mut top = TopK::new(n); 
+        // Always `Some`, since `parse` rejects overflowing groups
+        self.elves.iter().filter_map(Elf::total).for_each(|total| top.offer(total)); 
+        top.into_sorted_vec()
+    }
+}
+
+/**
+Running top-k of offered values, backed by a min-heap holding at most `k` values. 
+ */
+#[derive(Debug, Clone)]
+pub struct TopK {
+    k: usize, 
+    heap: BinaryHeap<Reverse<i64>>, 
+}
+
+impl TopK {
+    /// The heap grows with the offered values, so a huge `k` costs nothing up front.
+    pub fn new(k: usize) -> TopK {
+        TopK { k, heap: BinaryHeap::new() }
+    }
+
+    pub fn offer(&mut self, value: i64) {
+        if self.heap.len() < self.k {
+            self.heap.push(Reverse(value)); 
+        } else if self.heap.peek().is_some_and(|Reverse(min)| *min < value) {
+            self.heap.pop(); 
+            self.heap.push(Reverse(value)); 
         }
+    }
 
-        return Ok(max); 
+    /// Kept values in descending order.
+    pub fn into_sorted_vec(self) -> Vec<i64> {
+        // Ascending order of `Reverse` is descending order of values
+        self.heap.into_sorted_vec().into_iter().map(|Reverse(v)| v).collect()
     }
+}
 
-    fn get_star_2(input: &str) -> Result<i64, ()> {
-        let mut input = input.as_bytes(); 
-        let mut calories: Vec<i64> = Vec::new();
-        let mut curr = 0;  
-        loop {
-            let mut buf = String::new(); 
-            let result = input.read_line(&mut buf)
-                .expect("[day1::get_star_1] Error while reading line from input"); 
-
-            match buf.as_str() {
-                "\n" | "" => {
-                    calories.push(curr);  
-                    curr = 0;  
-                }, 
-                _ => {
-                    curr += buf.trim().parse::<i64>()
-                        .expect(format!("[day1::get_star_1] Input contains non-integer lines: {}", buf).as_str()); 
-                }
-            }
+#[derive(Debug)]
+pub enum AggregateError {
+    Io(io::Error), 
+    /// 1-based line number of a non-blank line that is not an integer.
+    NonInteger { line: usize }, 
+    /// 1-based line number at which the running sum of a group left the `i64` range.
+    Overflow { line: usize }, 
+}
 
-            if result == 0 {
-                break;
-            }
+impl fmt::Display for AggregateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AggregateError::Io(e) => write!(f, "cannot read input: {}", e), 
+            AggregateError::NonInteger { line } => write!(f, "non-integer input at line {}", line), 
+            AggregateError::Overflow { line } => write!(f, "group sum overflows `i64` at line {}", line), 
         }
+    }
+}
+
+/**
+Streams blank-line-separated groups of integers from `reader`, returning the `k` largest group sums 
+in descending order. 
 
-        calories.sort();
-        calories.reverse();  
-        if let (Some(a), Some(b), Some(c)) = (calories.get(0), calories.get(1), calories.get(2)) {
-            return Ok(a + b + c);
+Memory use is independent of the input size: only the current line, the current group's sum and 
+the running top-k are kept. Group sums are checked for overflow instead of wrapping.
+ */
+pub fn stream_top_k<R: BufRead>(mut reader: R, k: usize) -> Result<Vec<i64>, AggregateError> {
+    let mut top = TopK::new(k); 
+    let mut line = String::new(); 
+    let mut line_no: usize = 0; 
+    let mut group_sum: Option<i64> = None; 
+    loop {
+        line.clear(); 
+        let read_amnt = reader.read_line(&mut line).map_err(AggregateError::Io)?; 
+        line_no += 1; 
+        let trimmed = line.trim(); 
+        if trimmed.is_empty() {
+            if let Some(sum) = group_sum.take() { top.offer(sum); }
+            if read_amnt == 0 { return Ok(top.into_sorted_vec()); }
+            continue; 
         }
-        return Err(()); 
+
+        let item: i64 = trimmed.parse().map_err(|_| AggregateError::NonInteger { line: line_no })?; 
+        group_sum = Some(group_sum.unwrap_or(0).checked_add(item).ok_or(AggregateError::Overflow { line: line_no })?); 
+    }
+}
+
+pub struct Day1; 
+
+impl AOCSolutions for Day1 {
+    fn get_star_1(input: &str) -> Result<i64, ()> {
+        let top_1 = Day1::stream_top_k_or_report(input, 1, "get_star_1")?; 
+        top_1.first().copied().ok_or(())
+    }
+
+    fn get_star_2(input: &str) -> Result<i64, ()> {
+        let top_3 = Day1::stream_top_k_or_report(input, 3, "get_star_2")?; 
+        if top_3.len() < 3 { return Err(()); }
+        top_3.iter().try_fold(0i64, |acc, v| acc.checked_add(*v)).ok_or(())
+    }
+}
+
+impl Day1 {
+    fn stream_top_k_or_report(input: &str, k: usize, caller: &str) -> Result<Vec<i64>, ()> {
+        stream_top_k(input.as_bytes(), k).map_err(|e| eprintln!("[Day1::{}] Malformed input: {}", caller, e))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::Day1; 
+    use super::Inventory; 
+    use super::{stream_top_k, AggregateError}; 
     use super::AOCSolutions; 
 
     const SAMPLE_INPUT: &str = r"1000
@@ -96,5 +239,62 @@ mod tests {
     fn test_star_2_against_sample_input() {
         assert_eq!(Day1::get_star_2(SAMPLE_INPUT).unwrap(), 45000); 
     }
-}
 
+    #[test]
+    fn test_top_n() {
+        let inventory = Inventory::parse(SAMPLE_INPUT).unwrap(); 
+        assert_eq!(inventory.elves().len(), 5); 
+        assert_eq!(inventory.top_n(3), vec![24000, 11000, 10000]); 
+        assert_eq!(inventory.top_n(10), vec![24000, 11000, 10000, 6000, 4000]); 
+        assert!(inventory.top_n(0).is_empty()); 
+    }
+
+    #[test]
+    fn test_top_n_with_huge_n() {
+        let inventory = Inventory::parse("1\n\n2").unwrap(); 
+        assert_eq!(inventory.top_n(1 << 40), vec![2, 1]); 
+        assert_eq!(inventory.top_n(usize::MAX), vec![2, 1]); 
+        assert_eq!(stream_top_k("1\n\n2".as_bytes(), usize::MAX).unwrap(), vec![2, 1]); 
+    }
+
+    #[test]
+    fn test_elf_statistics() {
+        let inventory = Inventory::parse(SAMPLE_INPUT).unwrap(); 
+        let elf = &inventory.elves()[0]; 
+        assert_eq!(elf.count(), 3); 
+        assert_eq!(elf.mean(), Some(2000.0)); 
+        assert_eq!(elf.median(), Some(2000.0)); 
+        assert_eq!(inventory.elves()[2].median(), Some(5500.0)); 
+    }
+
+    #[test]
+    fn test_parse_rejects_non_integer() {
+        assert!(Inventory::parse("1000\nabc").is_none()); 
+    }
+
+    #[test]
+    fn test_stream_top_k() {
+        assert_eq!(stream_top_k(SAMPLE_INPUT.as_bytes(), 3).unwrap(), vec![24000, 11000, 10000]); 
+        assert_eq!(stream_top_k("\n\n1\n\n\n2\n".as_bytes(), 5).unwrap(), vec![2, 1]); 
+        assert!(matches!(stream_top_k("1\n\nx".as_bytes(), 1), Err(AggregateError::NonInteger { line: 3 }))); 
+    }
+
+    #[test]
+    fn test_stream_top_k_detects_overflow() {
+        let input = format!("1\n\n{}\n1", i64::MAX); 
+        assert!(matches!(stream_top_k(input.as_bytes(), 1), Err(AggregateError::Overflow { line: 4 }))); 
+        assert!(Day1::get_star_1(&input).is_err()); 
+        assert!(Inventory::parse(&input).is_none()); 
+    }
+
+    #[test]
+    fn test_elf_statistics_near_overflow() {
+        let input = format!("{}\n{}\n\n{}", i64::MAX, i64::MIN, i64::MAX); 
+        let inventory = Inventory::parse(&input).unwrap(); 
+        assert_eq!(inventory.elves()[0].total(), Some(-1)); 
+        assert_eq!(inventory.elves()[0].median(), Some(-0.5)); 
+        assert_eq!(inventory.top_n(2), vec![i64::MAX, -1]); 
+        let both_max = Inventory::parse(&format!("{}\n{}", i64::MAX, i64::MAX)); 
+        assert!(both_max.is_none()); 
+    }
+}
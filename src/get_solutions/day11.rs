@@ -1,4 +1,5 @@
 use super::AOCSolutions; 
+use crate::runner; 
 
 type Item = usize; 
 
@@ -152,7 +153,11 @@ impl AOCSolutions for Day11 {
     fn get_star_2(input: &str) -> Result<i64, ()> {
         let (mut monkeys, prod_div) = parse_monkeys(input); 
 
-        for _ in 1..=10000 as usize {
+        for round in 1..=10000 as usize {
+            if runner::is_cancelled() {
+                eprintln!("[Day11::get_star_2] Cancelled at round {}", round); 
+                return Err(()); 
+            }
             monkeys = play_round(monkeys, |item| { item % prod_div }); // Learned trick... I myself am not good at modular arithmetic
         }
 
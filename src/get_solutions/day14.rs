@@ -1,4 +1,5 @@
 use super::AOCSolutions; 
+use crate::runner; 
 use std::{collections::HashSet, cmp::{min, max}}; 
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
@@ -110,6 +111,10 @@ impl AOCSolutions for Day14 {
 
         let mut sand_unit_count: i64 = 0; 
         while let (sp, false) = find_bounded_sand_pos(&SAND_SOURCE, &blocked_set, abyss_bound) {
+            if runner::is_cancelled() {
+                eprintln!("[Day14::get_star_1] Cancelled after {} units of sand", sand_unit_count); 
+                return Err(()); 
+            }
             sand_unit_count += 1;
             if sp == SAND_SOURCE {
                 eprintln!("[Day14::get_star_1] Blocked sand source during iteration"); 
@@ -129,6 +134,10 @@ impl AOCSolutions for Day14 {
 
         let mut sand_unit_count: i64 = 0; 
         loop {
+            if runner::is_cancelled() {
+                eprintln!("[Day14::get_star_2] Cancelled after {} units of sand", sand_unit_count); 
+                return Err(()); 
+            }
             let (sp, _) = find_bounded_sand_pos(&SAND_SOURCE, &blocked_set, floor_bound); 
             sand_unit_count += 1;
             if sp == SAND_SOURCE { break; }
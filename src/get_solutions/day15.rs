@@ -1,6 +1,7 @@
 use std::collections::HashSet;
 
 use super::AOCSolutions; 
+use crate::runner; 
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
 struct Position {
@@ -173,6 +174,10 @@ impl AOCSolutions for Day15 {
       let known_beacons: HashSet<Position> = sensors.iter().map(|s| s.beacon_pos.clone()).collect(); 
 
       for sensor in sensors {
+         if runner::is_cancelled() {
+            eprintln!("[Day15::get_star_1] Cancelled"); 
+            return Err(()); 
+         }
          let impossibles = sensor.find_impossible_beacon_coords_along_axis(None, Some(y_axis)); 
          impossibles.into_iter().for_each(|p| { 
             if !known_beacons.contains(&p) { impossible_loc_set.insert(p); }
@@ -192,6 +197,10 @@ impl AOCSolutions for Day15 {
 
       let mut candidate_set: HashSet<Position> = HashSet::new(); 
       for eqn_1 in linear_eqns.iter() {
+         if runner::is_cancelled() {
+            eprintln!("[Day15::get_star_2] Cancelled while intersecting peripheries"); 
+            return Err(()); 
+         }
          for eqn_2 in linear_eqns.iter() {
             if let Some(p) = eqn_1.intersection(eqn_2) {
                if xy_range.contains(&p.x) && xy_range.contains(&p.y) { candidate_set.insert(p); } 
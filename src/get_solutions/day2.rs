@@ -1,151 +1,395 @@
 use super::AOCSolutions; 
-use std::io::BufRead; 
+use std::collections::HashMap; 
 
-struct Round<'a>(&'a Play, &'a Play);
+/**
+Rule table for the classic game: the first column of the strategy guide is the opponent's move, the 
+second either our move (star 1) or the desired outcome (star 2). 
+ */
+pub const CLASSIC_SPEC: &str = r"
+# move <name> <score> <symbols...>
+move Rock 1 A X
+move Paper 2 B Y
+move Scissors 3 C Z
 
-#[derive (PartialEq, Eq, Clone)] // Clone is unnecessary, just for the looks
-enum Play {
-    Rock, 
-    Paper, 
-    Scissors
-} 
+# beats <winner> <loser>
+beats Rock Scissors
+beats Paper Rock
+beats Scissors Paper
 
-impl <'a> Round<'a> {
-    fn get_score(&self) -> i64 {
-        let Round(my_play, other_play) = self; 
-        my_play.outcome(other_play) + my_play.get_pref_score()
+# outcome <win|draw|loss> <score> <symbols...>
+outcome loss 0 X
+outcome draw 3 Y
+outcome win 6 Z
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    Win, 
+    Draw, 
+    Loss, 
+}
+
+impl Outcome {
+    fn from_str(s: &str) -> Option<Outcome> {
+        match s {
+            "win" => Some(Outcome::Win), 
+            "draw" => Some(Outcome::Draw), 
+            "loss" => Some(Outcome::Loss), 
+            _ => None, 
+        }
     }
 }
 
-impl Play {
-    fn from_str(c: &str) -> Result<Play, ()> {
-        match c {
-            "A" | "X" => Ok(Play::Rock), 
-            "B" | "Y" => Ok(Play::Paper), 
-            "C" | "Z" => Ok(Play::Scissors), 
-            _ => Err(()), 
+#[derive(Debug, Clone)]
+struct Move {
+    name: String, 
+    score: i64, 
+}
+
+/**
+Data-driven description of a rock-paper-scissors-like game: the set of moves and their scores, the 
+dominance relation between moves, outcome scores and the guide symbols standing for moves/outcomes. 
+
+Any two distinct moves must be related in exactly one direction, e.g., Rock-Paper-Scissors-Lizard-Spock 
+is expressible but a game with "neutral" pairs is not. 
+ */
+#[derive(Debug, Clone)]
+pub struct RuleTable {
+    moves: Vec<Move>, 
+    beats: Vec<Vec<bool>>, // beats[a][b] <=> move `a` beats move `b`
+    outcome_scores: HashMap<Outcome, i64>, 
+    move_symbols: HashMap<String, usize>, 
+    outcome_symbols: HashMap<String, Outcome>, 
+}
+
+impl RuleTable {
+    pub fn classic() -> RuleTable {
+        RuleTable::parse(CLASSIC_SPEC).expect("[day2::RuleTable::classic] Malformed `CLASSIC_SPEC`")
+    }
+
+    /**
+    Parses a spec made of `move`, `beats` and `outcome` lines (see `CLASSIC_SPEC`). Blank lines and 
+    lines starting with `#` are ignored. Returns a message naming the offending line on error. 
+     */
+    pub fn parse(spec: &str) -> Result<RuleTable, String> {
+        let mut moves: Vec<Move> = Vec::new(); 
+        let mut beats_pairs: Vec<(String, String, usize)> = Vec::new(); 
+        let mut outcome_scores: HashMap<Outcome, i64> = HashMap::new(); 
+        let mut move_symbols: HashMap<String, usize> = HashMap::new(); 
+        let mut outcome_symbols: HashMap<String, Outcome> = HashMap::new(); 
+
+        for (idx, line) in spec.lines().enumerate() {
+            let line_no = idx + 1; 
+            let malformed = || format!("[day2::RuleTable::parse] Malformed line {}: \"{}\"", line_no, line.trim()); 
+            let words: Vec<&str> = line.split_whitespace().collect(); 
+            match words[..] {
+                [] => continue, 
+                [comment, ..] if comment.starts_with('#') => continue, 
+                ["move", name, score, ref symbols @ ..] => {
+                    if moves.iter().any(|m| m.name == name) {
+                        return Err(format!("[day2::RuleTable::parse] Duplicate move `{}` at line {}", name, line_no)); 
+                    }
+                    let score = score.parse().map_err(|_| malformed())?; 
+                    for symbol in symbols {
+                        if move_symbols.insert(symbol.to_string(), moves.len()).is_some() {
+                            return Err(format!("[day2::RuleTable::parse] Duplicate move symbol `{}` at line {}", symbol, line_no)); 
+                        }
+                    }
+                    moves.push(Move { name: name.to_string(), score }); 
+                }, 
+                ["beats", winner, loser] => beats_pairs.push((winner.to_string(), loser.to_string(), line_no)), 
+                ["outcome", outcome, score, ref symbols @ ..] => {
+                    let outcome = Outcome::from_str(outcome).ok_or_else(malformed)?; 
+                    outcome_scores.insert(outcome, score.parse().map_err(|_| malformed())?); 
+                    for symbol in symbols {
+                        if outcome_symbols.insert(symbol.to_string(), outcome).is_some() {
+                            return Err(format!("[day2::RuleTable::parse] Duplicate outcome symbol `{}` at line {}", symbol, line_no)); 
+                        }
+                    }
+                }, 
+                _ => return Err(malformed()), 
+            }
         }
+
+        let move_idx = |name: &str, line_no: usize| moves.iter().position(|m| m.name == name)
+            .ok_or_else(|| format!("[day2::RuleTable::parse] Undefined move `{}` at line {}", name, line_no)); 
+        let mut beats = vec![vec![false; moves.len()]; moves.len()]; 
+        for (winner, loser, line_no) in beats_pairs.iter() {
+            let (w, l) = (move_idx(winner, *line_no)?, move_idx(loser, *line_no)?); 
+            if w == l || beats[l][w] {
+                return Err(format!("[day2::RuleTable::parse] Contradicting dominance `{} > {}` at line {}", winner, loser, line_no)); 
+            }
+            beats[w][l] = true; 
+        }
+        for a in 0..moves.len() {
+            for b in (a + 1)..moves.len() {
+                if !beats[a][b] && !beats[b][a] {
+                    return Err(format!(
+                        "[day2::RuleTable::parse] No dominance between `{}` and `{}`", moves[a].name, moves[b].name
+                    )); 
+                }
+            }
+        }
+        if let Some(missing) = [Outcome::Win, Outcome::Draw, Outcome::Loss].iter().find(|o| !outcome_scores.contains_key(o)) {
+            return Err(format!("[day2::RuleTable::parse] Missing score for outcome {:?}", missing)); 
+        }
+
+        Ok(RuleTable { moves, beats, outcome_scores, move_symbols, outcome_symbols })
+    }
+
+    pub fn move_count(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn move_name(&self, mv: usize) -> &str {
+        &self.moves[mv].name
     }
 
-    fn get_pref_score(&self) -> i64 {
-        match self {
-            Self::Rock => 1, 
-            Self::Paper => 2, 
-            Self::Scissors => 3, 
+    pub fn move_for_symbol(&self, symbol: &str) -> Option<usize> {
+        self.move_symbols.get(symbol).copied()
+    }
+
+    pub fn outcome_for_symbol(&self, symbol: &str) -> Option<Outcome> {
+        self.outcome_symbols.get(symbol).copied()
+    }
+
+    /// Outcome of playing `mine` against `theirs`, from our point of view.
+    pub fn outcome(&self, mine: usize, theirs: usize) -> Outcome {
+        if mine == theirs {
+            Outcome::Draw
+        } else if self.beats[mine][theirs] {
+            Outcome::Win
+        } else {
+            Outcome::Loss
         }
     }
 
-    fn outcome(&self, other: &Play) -> i64 {
-        match self {
-            Self::Rock => {
-                match other {
-                    Self::Paper => 0,  
-                    Self::Rock => 3, 
-                    Self::Scissors => 6, 
-                }
-            }, 
-            Self::Paper => {
-                match other {
-                    Self::Scissors => 0, 
-                    Self::Paper => 3, 
-                    Self::Rock => 6, 
-                }
-            }, 
-            Self::Scissors => {
-                match other {
-                    Self::Rock => 0, 
-                    Self::Scissors => 3, 
-                    Self::Paper => 6, 
-                }
+    pub fn score(&self, mine: usize, theirs: usize) -> i64 {
+        self.moves[mine].score + self.outcome_scores[&self.outcome(mine, theirs)]
+    }
+
+    /// First move, in declaration order, achieving `desired` against `theirs`.
+    pub fn response_for(&self, theirs: usize, desired: Outcome) -> Option<usize> {
+        (0..self.moves.len()).find(|mine| self.outcome(*mine, theirs) == desired)
+    }
+
+    /**
+    Scores a strategy guide whose second column is our move. 
+     */
+    pub fn score_guide_as_moves(&self, guide: &str) -> Result<i64, String> {
+        self.score_guide(guide, |theirs, symbol| self.move_for_symbol(symbol).map(|mine| self.score(mine, theirs)))
+    }
+
+    /**
+    Scores a strategy guide whose second column is the desired outcome. 
+     */
+    pub fn score_guide_as_outcomes(&self, guide: &str) -> Result<i64, String> {
+        self.score_guide(guide, |theirs, symbol| {
+            let mine = self.response_for(theirs, self.outcome_for_symbol(symbol)?)?; 
+            Some(self.score(mine, theirs))
+        })
+    }
+
+    fn score_guide(&self, guide: &str, score_round: impl Fn(usize, &str) -> Option<i64>) -> Result<i64, String> {
+        let mut score: i64 = 0; 
+        for (idx, line) in guide.lines().enumerate() {
+            let invalid = || format!("[day2::RuleTable::score_guide] Invalid round at line {}: \"{}\"", idx + 1, line.trim()); 
+            match line.split_whitespace().collect::<Vec<&str>>()[..] {
+                [] => continue, 
+                [theirs, second] => {
+                    let theirs = self.move_for_symbol(theirs).ok_or_else(invalid)?; 
+                    score += score_round(theirs, second).ok_or_else(invalid)?; 
+                }, 
+                _ => return Err(invalid()), 
             }
         }
+        Ok(score)
     }
+}
+
+/**
+Meaning assigned to each distinct symbol of the strategy guide's second column. 
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Interpretation {
+    Moves(Vec<(String, usize)>), 
+    Outcomes(Vec<(String, Outcome)>), 
+}
 
-    fn lose_over(&self) -> Play {
-        match self {
-            Play::Rock => Self::Scissors, 
-            Play::Paper => Self::Rock,
-            Play::Scissors => Self::Paper, 
+#[derive(Debug, Clone)]
+pub struct MappingScore {
+    pub interpretation: Interpretation, 
+    /// Expected score of a single round drawn from the guide's distribution.
+    pub expected_per_round: f64, 
+    /// Expected score over as many rounds as the guide has.
+    pub expected_total: f64, 
+}
+
+/**
+Joint frequency distribution of (opponent move, second-column symbol), either over an actual 
+strategy guide or built from the two columns' frequencies. The opponent column's distribution is 
+its marginal. 
+ */
+#[derive(Debug, Clone)]
+pub struct GuideDistribution {
+    frequencies: HashMap<(usize, String), f64>, 
+    symbols: Vec<String>, 
+    rounds: usize, 
+}
+
+impl GuideDistribution {
+    pub fn from_guide(table: &RuleTable, guide: &str) -> Result<GuideDistribution, String> {
+        let mut counts: HashMap<(usize, String), usize> = HashMap::new(); 
+        let mut rounds: usize = 0; 
+        for (idx, line) in guide.lines().enumerate() {
+            let invalid = || format!("[day2::GuideDistribution::from_guide] Invalid round at line {}: \"{}\"", idx + 1, line.trim()); 
+            match line.split_whitespace().collect::<Vec<&str>>()[..] {
+                [] => continue, 
+                [theirs, second] => {
+                    let theirs = table.move_for_symbol(theirs).ok_or_else(invalid)?; 
+                    *counts.entry((theirs, second.to_string())).or_insert(0) += 1; 
+                    rounds += 1; 
+                }, 
+                _ => return Err(invalid()), 
+            }
         }
+
+        let mut symbols: Vec<String> = counts.keys().map(|(_, sym)| sym.clone()).collect(); 
+        symbols.sort(); 
+        symbols.dedup(); 
+        let frequencies = counts.into_iter()
+            .map(|(k, count)| (k, count as f64 / rounds.max(1) as f64))
+            .collect(); 
+        Ok(GuideDistribution { frequencies, symbols, rounds })
     }
 
-    fn draw_over(&self) -> Play {
-        return self.clone(); 
+    /**
+    Builds the distribution of `rounds` rounds from the opponent column's frequencies (indexed by 
+    move, see `RuleTable::move_for_symbol`) and the second column's `(symbol, frequency)` pairs, 
+    treating the two columns as independent. Frequencies are weights: each column is normalised to 
+    sum to 1. 
+     */
+    pub fn from_frequencies(table: &RuleTable, opponent: &[f64], symbols: &[(&str, f64)], rounds: usize) -> Result<GuideDistribution, String> {
+        let normalise = |weights: &[f64], column: &str| -> Result<Vec<f64>, String> {
+            let sum: f64 = weights.iter().sum(); 
+            if weights.iter().any(|w| !w.is_finite() || *w < 0.0) || sum <= 0.0 {
+                return Err(format!("[day2::GuideDistribution::from_frequencies] Invalid {} frequencies: {:?}", column, weights)); 
+            }
+            Ok(weights.iter().map(|w| w / sum).collect())
+        }; 
+        if opponent.len() != table.move_count() {
+            return Err(format!(
+                "[day2::GuideDistribution::from_frequencies] Expected {} opponent frequencies, found {}", table.move_count(), opponent.len()
+            )); 
+        }
+        let opponent = normalise(opponent, "opponent")?; 
+        let symbol_weights: Vec<f64> = symbols.iter().map(|(_, w)| *w).collect(); 
+        let symbol_freqs = normalise(&symbol_weights, "symbol")?; 
+
+        let mut names: Vec<String> = symbols.iter().map(|(sym, _)| sym.to_string()).collect(); 
+        names.sort(); 
+        names.dedup(); 
+        if names.len() != symbols.len() {
+            return Err("[day2::GuideDistribution::from_frequencies] Duplicate symbol".to_string()); 
+        }
+
+        let mut frequencies = HashMap::new(); 
+        for (theirs, opponent_freq) in opponent.iter().enumerate() {
+            for ((symbol, _), symbol_freq) in symbols.iter().zip(&symbol_freqs) {
+                let freq = opponent_freq * symbol_freq; 
+                // Impossible rounds cannot make a mapping unplayable
+                if freq > 0.0 { frequencies.insert((theirs, symbol.to_string()), freq); }
+            }
+        }
+        Ok(GuideDistribution { frequencies, symbols: names, rounds })
+    }
+
+    /// Marginal distribution of the opponent's moves, indexed by move.
+    pub fn opponent_frequencies(&self, table: &RuleTable) -> Vec<f64> {
+        let mut marginal = vec![0.0; table.move_count()]; 
+        self.frequencies.iter().for_each(|((theirs, _), freq)| marginal[*theirs] += freq); 
+        marginal
+    }
+
+    fn score(&self, interpretation: Interpretation, round_score: impl Fn(usize, &str) -> Option<i64>) -> Option<MappingScore> {
+        let mut expected_per_round = 0.0; 
+        for ((theirs, symbol), freq) in self.frequencies.iter() {
+            expected_per_round += freq * round_score(*theirs, symbol)? as f64; 
+        }
+        Some(MappingScore { interpretation, expected_per_round, expected_total: expected_per_round * self.rounds as f64 })
     }
 
-    fn win_over(&self) -> Play {
-        match self {
-            Play::Rock => Self::Paper, 
-            Play::Paper => Self::Scissors, 
-            Play::Scissors => Self::Rock, 
+    /**
+    Scores every injective mapping of the guide's second-column symbols onto moves and onto 
+    outcomes, best first. Mappings under which some round is unplayable (e.g., no move achieves the 
+    desired outcome) are left out. 
+     */
+    pub fn rank_mappings(&self, table: &RuleTable) -> Vec<MappingScore> {
+        let lookup = |mapping: &[(String, usize)], symbol: &str| mapping.iter().find(|(s, _)| s == symbol).map(|(_, v)| *v); 
+        let mut scores: Vec<MappingScore> = Vec::new(); 
+
+        for assignment in injections(self.symbols.len(), table.move_count()) {
+            let mapping: Vec<(String, usize)> = self.symbols.iter().cloned().zip(assignment).collect(); 
+            let score = self.score(Interpretation::Moves(mapping.clone()), |theirs, symbol| {
+                Some(table.score(lookup(&mapping, symbol)?, theirs))
+            }); 
+            scores.extend(score); 
+        }
+
+        const OUTCOMES: [Outcome; 3] = [Outcome::Loss, Outcome::Draw, Outcome::Win]; 
+        for assignment in injections(self.symbols.len(), OUTCOMES.len()) {
+            let mapping: Vec<(String, usize)> = self.symbols.iter().cloned().zip(assignment).collect(); 
+            let interpretation = Interpretation::Outcomes(mapping.iter().map(|(s, o)| (s.clone(), OUTCOMES[*o])).collect()); 
+            let score = self.score(interpretation, |theirs, symbol| {
+                let mine = table.response_for(theirs, OUTCOMES[lookup(&mapping, symbol)?])?; 
+                Some(table.score(mine, theirs))
+            }); 
+            scores.extend(score); 
+        }
+
+        scores.sort_by(|a, b| b.expected_per_round.total_cmp(&a.expected_per_round)); 
+        scores
+    }
+}
+
+/**
+All injective maps from `0..k` to `0..n`, each given as the list of images. 
+ */
+fn injections(k: usize, n: usize) -> Vec<Vec<usize>> {
+    fn extend(prefix: &mut Vec<usize>, k: usize, n: usize, result: &mut Vec<Vec<usize>>) {
+        if prefix.len() == k { 
+            result.push(prefix.clone()); 
+            return; 
+        }
+        for v in 0..n {
+            if prefix.contains(&v) { continue; }
+            prefix.push(v); 
+            extend(prefix, k, n, result); 
+            prefix.pop(); 
         }
     }
+
+    let mut result = Vec::new(); 
+    if k <= n { extend(&mut Vec::with_capacity(k), k, n, &mut result); }
+    result
 }
 
 pub struct Day2; 
 
 impl AOCSolutions for Day2 {
     fn get_star_1(input: &str) -> Result<i64, ()> {
-        let mut input = input.as_bytes();
-        let mut score: i64 = 0;
-        let mut buf = String::new();  
-        loop {
-            let _ = input.read_line(&mut buf)
-                .expect("[Day2::get_star_1] Error while reading line from input"); 
-            
-            match buf.as_str() {
-                "" => return Ok(score), 
-                _ => {
-                    // There must be a better way...
-                    let plays: Vec<Play> = buf.trim().split(' ')
-                        .take(2)
-                        .map(|s| { 
-                            Play::from_str(s)
-                                .expect(format!(
-                                    "[Day2::get_star_2] Invalid substring in input: {}", s
-                                ).as_str()) 
-                            }
-                        )
-                        .collect();
-                    let round = Round(&plays[1], &plays[0]); 
-                    score += round.get_score(); 
-                }
-            }
-            buf.clear(); 
-        }
+        RuleTable::classic().score_guide_as_moves(input).map_err(|e| eprintln!("{}", e))
     }
 
     fn get_star_2(input: &str) -> Result<i64, ()> {
-        let mut input = input.as_bytes();
-        let mut score: i64 = 0;
-        let mut buf = String::new();  
-        loop {
-            let _ = input.read_line(&mut buf)
-                .expect("[Day2::get_star_1] Error while reading line from input"); 
-            
-            match buf.as_str() {
-                "" => return Ok(score), 
-                _ => {
-                    let config: Vec<&str> = buf.trim().split(' ').take(2).collect();
-                    let other_play = Play::from_str(config[0]).unwrap();  
-                    let this_play = match config[1] {
-                        "X" => Play::lose_over(&other_play), 
-                        "Y" => Play::draw_over(&other_play), 
-                        "Z" => Play::win_over(&other_play), 
-                        a => panic!("[Day2::get_star_1] Invalid substring in input: {}", a)
-                    }; 
-                    let round = Round(&this_play, &other_play); 
-                    score += round.get_score(); 
-                }
-            }
-            buf.clear(); 
-        }
+        RuleTable::classic().score_guide_as_outcomes(input).map_err(|e| eprintln!("{}", e))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::Day2; 
+    use super::{GuideDistribution, Interpretation, Outcome, RuleTable}; 
     use super::AOCSolutions; 
 
     const SAMPLE_INPUT: &str = r"A Y
@@ -162,4 +406,103 @@ mod tests {
     fn test_star_2_against_sample_input() {
         assert_eq!(Day2::get_star_2(SAMPLE_INPUT).unwrap(), 12)
     }
-}
\ No newline at end of file
+
+    const RPSLS_SPEC: &str = r"
+move Rock 1 A
+move Paper 2 B
+move Scissors 3 C
+move Lizard 4 D
+move Spock 5 E
+beats Scissors Paper
+beats Paper Rock
+beats Rock Lizard
+beats Lizard Spock
+beats Spock Scissors
+beats Scissors Lizard
+beats Lizard Paper
+beats Paper Spock
+beats Spock Rock
+beats Rock Scissors
+outcome loss 0 L
+outcome draw 3 D
+outcome win 6 W";
+
+    #[test]
+    fn test_rpsls() {
+        let table = RuleTable::parse(RPSLS_SPEC).unwrap(); 
+        let (rock, spock, lizard) = (0, 4, 3); 
+        assert_eq!(table.outcome(spock, rock), Outcome::Win); 
+        assert_eq!(table.outcome(lizard, rock), Outcome::Loss); 
+        assert_eq!(table.move_name(table.response_for(rock, Outcome::Win).unwrap()), "Paper"); 
+        assert_eq!(table.score_guide_as_moves("A E\nE D").unwrap(), (5 + 6) + (4 + 6)); 
+        assert_eq!(table.score_guide_as_outcomes("A W\nC D").unwrap(), (2 + 6) + (3 + 3)); 
+    }
+
+    #[test]
+    fn test_parse_rejects_incomplete_specs() {
+        assert!(RuleTable::parse("move Rock 1\nmove Paper 2\noutcome win 6\noutcome draw 3\noutcome loss 0").is_err()); 
+        assert!(RuleTable::parse("move Rock 1\nmove Paper 2\nbeats Paper Rock\nbeats Rock Paper").is_err()); 
+        assert!(RuleTable::parse("move Rock x").is_err()); 
+        assert!(RuleTable::classic().score_guide_as_moves("A Q").is_err()); 
+    }
+
+    #[test]
+    fn test_rank_mappings() {
+        let table = RuleTable::classic(); 
+        let distribution = GuideDistribution::from_guide(&table, SAMPLE_INPUT).unwrap(); 
+        let third = 1.0 / 3.0; 
+        assert_eq!(distribution.opponent_frequencies(&table), vec![third, third, third]); 
+
+        let ranked = distribution.rank_mappings(&table); 
+        assert_eq!(ranked.len(), 6 + 6); 
+        let total_of = |interpretation: &Interpretation| ranked.iter()
+            .find(|m| &m.interpretation == interpretation)
+            .map(|m| m.expected_total.round() as i64)
+            .unwrap(); 
+        let as_given_moves = Interpretation::Moves(vec![("X".to_string(), 0), ("Y".to_string(), 1), ("Z".to_string(), 2)]); 
+        let as_given_outcomes = Interpretation::Outcomes(vec![
+            ("X".to_string(), Outcome::Loss), ("Y".to_string(), Outcome::Draw), ("Z".to_string(), Outcome::Win), 
+        ]); 
+        assert_eq!(total_of(&as_given_moves), 15); 
+        assert_eq!(total_of(&as_given_outcomes), 12); 
+
+        // Every round won: 3 * 6 plus the winning moves' scores (Paper + Scissors + Rock)
+        assert_eq!(ranked[0].expected_total.round() as i64, 18 + 2 + 3 + 1); 
+        assert!(ranked.windows(2).all(|w| w[0].expected_per_round >= w[1].expected_per_round)); 
+    }
+
+    #[test]
+    fn test_rank_mappings_from_frequencies() {
+        let table = RuleTable::classic(); 
+        // Rock half of the time; a single symbol is best read as "win"
+        let distribution = GuideDistribution::from_frequencies(&table, &[2.0, 1.0, 1.0], &[("X", 1.0)], 4).unwrap(); 
+        assert_eq!(distribution.opponent_frequencies(&table), vec![0.5, 0.25, 0.25]); 
+        let ranked = distribution.rank_mappings(&table); 
+        assert_eq!(ranked.len(), 3 + 3); 
+        assert_eq!(ranked[0].interpretation, Interpretation::Outcomes(vec![("X".to_string(), Outcome::Win)])); 
+        assert_eq!(ranked[0].expected_per_round, 0.5 * 8.0 + 0.25 * 9.0 + 0.25 * 7.0); 
+        assert_eq!(ranked[0].expected_total, 4.0 * 8.0); 
+        // Paper: wins against Rock, draws against Paper, loses against Scissors
+        assert_eq!(ranked[1].interpretation, Interpretation::Moves(vec![("X".to_string(), 1)])); 
+        assert_eq!(ranked[1].expected_per_round, 0.5 * 8.0 + 0.25 * 5.0 + 0.25 * 2.0); 
+
+        // Always Rock, X and Y equally often: the columns are combined as independent
+        let distribution = GuideDistribution::from_frequencies(&table, &[1.0, 0.0, 0.0], &[("X", 0.5), ("Y", 0.5)], 10).unwrap(); 
+        let ranked = distribution.rank_mappings(&table); 
+        assert_eq!(ranked.len(), 6 + 6); 
+        let score_of = |interpretation: Interpretation| ranked.iter().find(|m| m.interpretation == interpretation).unwrap().expected_per_round; 
+        assert_eq!(score_of(Interpretation::Moves(vec![("X".to_string(), 2), ("Y".to_string(), 0)])), 0.5 * 3.0 + 0.5 * 4.0); 
+        assert_eq!(ranked[0].expected_per_round, 0.5 * 8.0 + 0.5 * 4.0); 
+        assert_eq!(ranked[0].expected_total, 60.0); 
+    }
+
+    #[test]
+    fn test_from_frequencies_rejects_invalid_input() {
+        let table = RuleTable::classic(); 
+        assert!(GuideDistribution::from_frequencies(&table, &[1.0, 1.0], &[("X", 1.0)], 1).is_err()); 
+        assert!(GuideDistribution::from_frequencies(&table, &[1.0, -1.0, 1.0], &[("X", 1.0)], 1).is_err()); 
+        assert!(GuideDistribution::from_frequencies(&table, &[0.0, 0.0, 0.0], &[("X", 1.0)], 1).is_err()); 
+        assert!(GuideDistribution::from_frequencies(&table, &[1.0, 1.0, 1.0], &[], 1).is_err()); 
+        assert!(GuideDistribution::from_frequencies(&table, &[1.0, 1.0, 1.0], &[("X", 1.0), ("X", 1.0)], 1).is_err()); 
+    }
+}
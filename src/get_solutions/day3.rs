@@ -1,94 +1,337 @@
-use std::io::BufRead;
-use std::collections::HashSet; 
+use std::fmt;
+use std::thread;
 
 use super::AOCSolutions; 
 
+/**
+Set of rucksack item types packed into a `u64`, where bit `p` is set iff the item of priority `p` 
+(`a..=z` => 1..=26, `A..=Z` => 27..=52) is present. 
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ItemSet(u64); 
+
+impl ItemSet {
+    /// Set of every item type.
+    pub const ALL: ItemSet = ItemSet(((1u64 << 52) - 1) << 1); 
+
+    /**
+    Collects `items` into a set. Returns `None` if any byte is not an item type (ASCII letter). 
+     */
+    pub fn from_items(items: &[u8]) -> Option<ItemSet> {
+        let mut bits: u64 = 0; 
+        for item in items {
+            bits |= 1 << Day3::priority(item).ok()?; 
+        }
+        Some(ItemSet(bits))
+    }
+
+    pub fn intersection(self, other: ItemSet) -> ItemSet {
+        ItemSet(self.0 & other.0)
+    }
+
+    pub fn union(self, other: ItemSet) -> ItemSet {
+        ItemSet(self.0 | other.0)
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    /// Sum of the priorities of all item types in the set.
+    pub fn priority_sum(self) -> i64 {
+        let mut bits = self.0; 
+        let mut sum: i64 = 0; 
+        while bits != 0 {
+            sum += bits.trailing_zeros() as i64; 
+            bits &= bits - 1; 
+        }
+        sum
+    }
+}
+
+impl fmt::Display for ItemSet {
+    /// Item types as letters, in priority order.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (b'a'..=b'z').chain(b'A'..=b'Z')
+            .filter(|u| self.0 & (1 << Day3::priority(u).unwrap()) != 0)
+            .try_for_each(|u| write!(f, "{}", u as char))
+    }
+}
+
+/**
+Group of rucksacks whose common-item set is not a single badge.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupIssue {
+    /// 1-based line numbers of the group's rucksacks.
+    pub lines: Vec<usize>, 
+    /// Items common to all rucksacks of the group; empty or with more than one item.
+    pub common: ItemSet, 
+}
+
+impl fmt::Display for GroupIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.common.is_empty() {
+            write!(f, "group at lines {:?} has no common item", self.lines)
+        } else {
+            write!(f, "group at lines {:?} has {} common items \"{}\"", self.lines, self.common.len(), self.common)
+        }
+    }
+}
+
+/**
+Outcome of splitting rucksacks into groups and looking for each group's badge. 
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BadgeReport {
+    /// Sum of badge priorities over groups with exactly one common item.
+    pub sum: i64, 
+    /// Groups without exactly one common item, in input order.
+    pub issues: Vec<GroupIssue>, 
+    /// 1-based line numbers of a trailing group with fewer rucksacks than the group size.
+    pub partial_group: Option<Vec<usize>>, 
+}
+
+impl BadgeReport {
+    /// Whether every rucksack belongs to a complete group with a single badge.
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty() && self.partial_group.is_none()
+    }
+}
+
 pub struct Day3; 
 
 impl AOCSolutions for Day3 {
-    // Can be parallelized. Maybe work on it later? 
     fn get_star_1(input: &str) -> Result<i64, ()> {
-        let mut input = input.as_bytes(); 
-        let mut line = String::new(); 
+        Day3::compartment_sum(input)
+    }
+
+    fn get_star_2(input: &str) -> Result<i64, ()> {
+        let report = Day3::badge_report(input, 3).ok_or(())?; 
+        for issue in report.issues.iter() {
+            eprintln!("[Day3::get_star_2] Malformed input: {}", issue); 
+        }
+        if let Some(lines) = report.partial_group.as_ref() {
+            eprintln!("[Day3::get_star_2] Malformed input: incomplete group at lines {:?}", lines); 
+        }
+        if !report.is_clean() { return Err(()); }
+        Ok(report.sum)
+    }
+}
+
+impl Day3 {
+    /**
+    Splits the non-blank lines of `input` into consecutive groups of `group_size` rucksacks and sums 
+    the priorities of the groups' badges, recording every group without exactly one common item and 
+    any incomplete trailing group. 
+
+    Returns `None` if `group_size` is 0 or a rucksack contains a non-letter. 
+     */
+    pub fn badge_report(input: &str, group_size: usize) -> Option<BadgeReport> {
+        if group_size == 0 {
+            eprintln!("[Day3::badge_report] Group size must be positive"); 
+            return None; 
+        }
+        Day3::badge_report_from(input, 1, group_size)
+    }
+
+    /**
+    Parallel `get_star_1` on up to `threads` scoped threads, each summing a line-aligned chunk. 
+     */
+    #[allow(clippy::result_unit_err)] // Mirrors `AOCSolutions`
+    pub fn get_star_1_parallel(input: &str, threads: usize) -> Result<i64, ()> {
+        let chunks = split_lines(input, threads); 
+        if chunks.len() <= 1 { return Day3::get_star_1(input); }
+
+        let sums: Vec<Result<i64, ()>> = thread::scope(|scope| {
+            let handles: Vec<_> = chunks.iter()
+                .map(|chunk| scope.spawn(move || Day3::compartment_sum(chunk)))
+                .collect(); 
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        }); 
+        sums.into_iter().sum()
+    }
+
+    /**
+    Parallel `get_star_2` on up to `threads` scoped threads, see `badge_report_parallel`. 
+     */
+    #[allow(clippy::result_unit_err)] // Mirrors `AOCSolutions`
+    pub fn get_star_2_parallel(input: &str, threads: usize) -> Result<i64, ()> {
+        let report = Day3::badge_report_parallel(input, 3, threads).ok_or(())?; 
+        if !report.is_clean() {
+            eprintln!("[Day3::get_star_2_parallel] Malformed input: {} bad group(s)", 
+                report.issues.len() + report.partial_group.iter().count()); 
+            return Err(()); 
+        }
+        Ok(report.sum)
+    }
+
+    /**
+    Parallel `badge_report` on up to `threads` scoped threads. The input is split into line-aligned 
+    chunks, which are then moved forward to the next group boundary so that no group straddles two 
+    chunks; the chunk reports are merged in input order, so the result equals `badge_report`'s. 
+     */
+    pub fn badge_report_parallel(input: &str, group_size: usize, threads: usize) -> Option<BadgeReport> {
+        if group_size == 0 {
+            eprintln!("[Day3::badge_report_parallel] Group size must be positive"); 
+            return None; 
+        }
+        let chunks = split_lines(input, threads); 
+        if chunks.len() <= 1 { return Day3::badge_report(input, group_size); }
+
+        // (lines, rucksacks) per chunk, to restore line numbers and find group boundaries
+        let counts: Vec<(usize, usize)> = thread::scope(|scope| {
+            let handles: Vec<_> = chunks.iter()
+                .map(|chunk| scope.spawn(move || (
+                    chunk.lines().count(), 
+                    chunk.lines().filter(|l| !l.trim().is_empty()).count(), 
+                )))
+                .collect(); 
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        }); 
+
+        // (byte offset, 1-based line number) at which each aligned segment starts
+        let mut starts: Vec<(usize, usize)> = Vec::with_capacity(chunks.len()); 
+        let (mut offset, mut line_count, mut rucksack_count) = (0, 0, 0); 
+        for (chunk, (lines, rucksacks)) in chunks.iter().zip(counts) {
+            let to_skip = (group_size - rucksack_count % group_size) % group_size; 
+            let (skipped_bytes, skipped_lines) = skip_rucksacks(&input[offset..], to_skip); 
+            let start = (offset + skipped_bytes, line_count + skipped_lines + 1); 
+            if start.0 < input.len() && starts.last().is_none_or(|last| last.0 < start.0) { starts.push(start); }
+            offset += chunk.len(); 
+            line_count += lines; 
+            rucksack_count += rucksacks; 
+        }
+        let ends = starts.iter().skip(1).map(|(offset, _)| *offset).chain([input.len()]); 
+        let segments: Vec<(&str, usize)> = starts.iter().zip(ends)
+            .map(|(&(bgn, first_line), end)| (&input[bgn..end], first_line))
+            .collect(); 
+
+        let reports: Vec<Option<BadgeReport>> = thread::scope(|scope| {
+            let handles: Vec<_> = segments.iter()
+                .map(|&(segment, first_line)| scope.spawn(move || Day3::badge_report_from(segment, first_line, group_size)))
+                .collect(); 
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        }); 
+
+        let mut merged = BadgeReport::default(); 
+        for report in reports {
+            let report = report?; 
+            merged.sum += report.sum; 
+            merged.issues.extend(report.issues); 
+            // Only the last segment can end mid-group
+            merged.partial_group = report.partial_group; 
+        }
+        Some(merged)
+    }
+
+    /// Sum over rucksacks of the priorities of the items in both compartments.
+    fn compartment_sum(input: &str) -> Result<i64, ()> {
         let mut sum: i64 = 0; 
-        loop {
-            let _ = input.read_line(&mut line)
-                .expect("[Day3::get_star_1] Error while reading line from `input`");
-            let line_u8 = line.trim().as_bytes(); 
+        for line in input.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let line_u8 = line.as_bytes(); 
             let divisor = line_u8.len() / 2; // Assumes len % 2 == 0
-            if divisor == 0 {
-                return Ok(sum); 
-            }
-            
-            let (compartment_1, compartment_2): (HashSet<&u8>, HashSet<&u8>) = (
-                HashSet::from_iter(line_u8[..divisor].iter()), 
-                HashSet::from_iter(line_u8[divisor..].iter())
-            );
-            
-            let intersection = compartment_1.intersection(&compartment_2)
-                .filter(|u| u.is_ascii()); 
-
-            sum += intersection
-                .map(|u| Day3::priority(u).unwrap())
-                .sum::<i64>(); 
-
-            line.clear(); 
+            let (compartment_1, compartment_2) = match (
+                ItemSet::from_items(&line_u8[..divisor]), 
+                ItemSet::from_items(&line_u8[divisor..])
+            ) {
+                (Some(c1), Some(c2)) => (c1, c2), 
+                _ => {
+                    eprintln!("[Day3::compartment_sum] Invalid item in rucksack \"{}\"", line); 
+                    return Err(()); 
+                }, 
+            }; 
+            sum += compartment_1.intersection(compartment_2).priority_sum(); 
         }
+        Ok(sum)
     }
 
-    fn get_star_2(input: &str) -> Result<i64, ()> {
-        let mut input = input.as_bytes();
-        let mut read_amnt: usize; 
-        let mut sum: i64 = 0;
-        loop {
-            // Set up buffers
-            let mut bufs = [String::new(), String::new(), String::new()]; 
-            for buf in bufs.iter_mut() {
-                read_amnt = input.read_line(buf)
-                    .expect("[Day3::get_star_2] Error while reading line from `input`"); 
-                if read_amnt == 0 { 
-                    // EOF passed (cannot read more) => return sum, ignore anything already read in triplet.
-                    return Ok(sum); 
-                }
-            }
+    /// `badge_report` of `input` whose first line is line `first_line` of the whole input.
+    fn badge_report_from(input: &str, first_line: usize, group_size: usize) -> Option<BadgeReport> {
+        let mut report = BadgeReport::default(); 
+        let mut lines: Vec<usize> = Vec::with_capacity(group_size); 
+        let mut common = ItemSet::ALL; 
+        for (idx, line) in input.lines().enumerate() {
+            let line = line.trim(); 
+            let line_no = first_line + idx; 
+            if line.is_empty() { continue; }
+            let Some(items) = ItemSet::from_items(line.as_bytes()) else {
+                eprintln!("[Day3::badge_report] Invalid item in rucksack \"{}\" at line {}", line, line_no); 
+                return None; 
+            }; 
+            common = common.intersection(items); 
+            lines.push(line_no); 
+            if lines.len() < group_size { continue; }
 
-            // Find common `u8` in buffers
-            if let Some(common) = bufs.iter()
-                .map(|s| HashSet::<&u8>::from_iter(s.trim().as_bytes().iter()))
-                .reduce(|partial_intersection, rhs| partial_intersection.intersection(&rhs).cloned().collect()) {
-                if common.is_empty() || common.len() > 1 { 
-                    return Err(()); 
-                } // else, guaranteed singleton
-                let badge = common.iter().next().unwrap(); 
-                sum += Day3::priority(badge)
-                    .expect("[Day3::get_star_2] Invalid `u8` in `input`"); 
-            } else { // iterator empty somehow
-                panic!("[Day3::get_star_2] Empty iterator after conversion to `HashSet<&u8>` -- This should not happen"); 
-            } 
+            if common.len() == 1 {
+                report.sum += common.priority_sum(); 
+                lines.clear(); 
+            } else {
+                report.issues.push(GroupIssue { lines: std::mem::take(&mut lines), common }); 
+            }
+            common = ItemSet::ALL; 
         }
+        if !lines.is_empty() { report.partial_group = Some(lines); }
+        Some(report)
     }
-}
 
-impl Day3 {
     fn priority(ascii_u8: &u8) -> Result<i64, ()> {
         const UPPER_A_U8: u8 = 0x41u8; 
         const LOWER_A_U8: u8 = 0x61u8;
 
-        if ascii_u8.is_ascii() {
-            match ascii_u8.is_ascii_uppercase() {
-                true => return Ok((ascii_u8 - UPPER_A_U8 + 27).into()), 
-                false => return Ok((ascii_u8 - LOWER_A_U8 + 1).into()),  
-            }
+        match ascii_u8 {
+            u if u.is_ascii_uppercase() => Ok((u - UPPER_A_U8 + 27).into()), 
+            u if u.is_ascii_lowercase() => Ok((u - LOWER_A_U8 + 1).into()), 
+            _ => Err(()), 
         }
-        return Err(()); 
     }
 }
 
+/**
+Splits `input` into at most `count` chunks of similar byte length, each ending right after a 
+newline (except possibly the last one). 
+ */
+fn split_lines(input: &str, count: usize) -> Vec<&str> {
+    let target_len = input.len().div_ceil(count.max(1)).max(1); 
+    let mut chunks = Vec::with_capacity(count); 
+    let mut rest = input; 
+    while !rest.is_empty() {
+        let end = match rest.as_bytes().iter().skip(target_len - 1).position(|u| *u == b'\n') {
+            Some(pos) => target_len + pos, 
+            None => rest.len(), 
+        }; 
+        let (chunk, tail) = rest.split_at(end.min(rest.len())); 
+        chunks.push(chunk); 
+        rest = tail; 
+    }
+    chunks
+}
+
+/**
+Returns the number of bytes and lines to skip at the start of `input` to pass `count` rucksacks 
+(non-blank lines), or the whole of `input` if it has fewer. 
+ */
+fn skip_rucksacks(input: &str, count: usize) -> (usize, usize) {
+    let (mut bytes, mut lines, mut rucksacks) = (0, 0, 0); 
+    for line in input.split_inclusive('\n') {
+        if rucksacks == count { break; }
+        if !line.trim().is_empty() { rucksacks += 1; }
+        bytes += line.len(); 
+        lines += 1; 
+    }
+    (bytes, lines)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::get_solutions::AOCSolutions;
 
     use super::Day3;
+    use super::{GroupIssue, ItemSet};
 
     const SAMPLE_INPUT: &str = r"vJrwpWtwJgWrhcsFMMfFFhFp
     jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
@@ -106,4 +349,51 @@ mod tests {
     fn test_get_star_2() {
         assert_eq!(Day3::get_star_2(SAMPLE_INPUT).unwrap(), 70)
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_item_set() {
+        let a = ItemSet::from_items(b"vJrwpWtwJgWr").unwrap(); 
+        let b = ItemSet::from_items(b"hcsFMMfFFhFp").unwrap(); 
+        assert_eq!(a.intersection(b), ItemSet::from_items(b"p").unwrap()); 
+        assert_eq!(a.intersection(b).priority_sum(), 16); 
+        assert_eq!(a.union(b).len(), 8 + 7 - 1); 
+        assert_eq!(ItemSet::ALL.len(), 52); 
+        assert_eq!(ItemSet::ALL.priority_sum(), (1..=52).sum::<i64>()); 
+        assert!(ItemSet::from_items(b"ab1").is_none()); 
+        assert!(ItemSet::default().is_empty()); 
+    }
+
+    #[test]
+    fn test_badge_report() {
+        let report = Day3::badge_report(SAMPLE_INPUT, 3).unwrap(); 
+        assert!(report.is_clean()); 
+        assert_eq!(report.sum, 70); 
+
+        // Lines 1-2 share "r" and "s", lines 3-4 share nothing, line 5 is left over
+        let report = Day3::badge_report("abcrs\nrsxyz\nabc\n\nxyz\nqqq", 2).unwrap(); 
+        assert_eq!(report.sum, 0); 
+        assert_eq!(report.issues, vec![
+            GroupIssue { lines: vec![1, 2], common: ItemSet::from_items(b"rs").unwrap() }, 
+            GroupIssue { lines: vec![3, 5], common: ItemSet::default() }, 
+        ]); 
+        assert_eq!(report.partial_group, Some(vec![6])); 
+        assert_eq!(report.issues[0].to_string(), "group at lines [1, 2] has 2 common items \"rs\""); 
+        assert!(Day3::get_star_2("abc\nabc").is_err()); 
+        assert!(Day3::badge_report(SAMPLE_INPUT, 0).is_none()); 
+    }
+
+    #[test]
+    fn test_parallel_matches_sequential() {
+        let input = format!("{}\n\nabc\nxyzzy\n{}\nqq\n", SAMPLE_INPUT, SAMPLE_INPUT); 
+        let sequential = Day3::badge_report(&input, 3).unwrap(); 
+        assert!(!sequential.issues.is_empty()); 
+        for threads in 1..=8 {
+            assert_eq!(Day3::get_star_1_parallel(SAMPLE_INPUT, threads).unwrap(), 157); 
+            assert_eq!(Day3::get_star_2_parallel(SAMPLE_INPUT, threads).unwrap(), 70); 
+            assert_eq!(Day3::get_star_1_parallel(&input, threads), Day3::get_star_1(&input)); 
+            for group_size in 1..=4 {
+                assert_eq!(Day3::badge_report_parallel(&input, group_size, threads), Day3::badge_report(&input, group_size)); 
+            }
+        }
+    }
+}
@@ -1,9 +1,83 @@
 use super::AOCSolutions; 
+use std::fmt; 
 use std::ops::RangeInclusive; 
 
 pub struct Day4; 
 type RangeInclDuo = (RangeInclusive<i64>, RangeInclusive<i64>); 
 
+/**
+Elf identified by the 1-based input line of its pair and its position (0 or 1) within the pair. 
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ElfId {
+    pub line: usize, 
+    pub slot: usize, 
+}
+
+/**
+How `Day4::parse_assignments` treats reversed ranges such as `9-3`. 
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParseMode {
+    /// Reject reversed ranges.
+    #[default]
+    Strict, 
+    /// Normalise reversed ranges, e.g., `9-3` to `3-9`.
+    Lenient, 
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// Expected a section ID, found the given token (possibly empty).
+    NotANumber(String), 
+    /// Section IDs cannot be negative.
+    Negative(String), 
+    /// Expected the given separator, found something else or the end of the line.
+    MissingSeparator(char), 
+    /// Range whose start is greater than its end (strict mode only).
+    Reversed(i64, i64), 
+    /// Unexpected text after the second range.
+    TrailingGarbage(String), 
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based line number.
+    pub line: usize, 
+    pub kind: ParseErrorKind, 
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?; 
+        match &self.kind {
+            ParseErrorKind::NotANumber(token) => write!(f, "expected a section ID, found \"{}\"", token), 
+            ParseErrorKind::Negative(token) => write!(f, "negative section ID \"{}\"", token), 
+            ParseErrorKind::MissingSeparator(sep) => write!(f, "expected '{}'", sep), 
+            ParseErrorKind::Reversed(start, end) => write!(f, "reversed range {}-{}", start, end), 
+            ParseErrorKind::TrailingGarbage(rest) => write!(f, "unexpected trailing \"{}\"", rest), 
+        }
+    }
+}
+
+/**
+Analysis of all section assignments at once, rather than pair by pair. 
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct OverlapAnalysis {
+    /// Elves whose range overlaps the range of any other elf (partner included), in input order.
+    pub overlapping_elves: Vec<ElfId>, 
+    /// Number of elves assigned to each section, as maximal runs of sections with equal coverage 
+    /// from the lowest assigned section to the highest one.
+    pub coverage: Vec<(RangeInclusive<i64>, usize)>, 
+    /// Highest number of elves assigned to a single section.
+    pub max_coverage: usize, 
+    /// Sections assigned to `max_coverage` elves.
+    pub busiest_sections: Vec<RangeInclusive<i64>>, 
+    /// Sections between the lowest and highest assigned section that nobody is assigned to.
+    pub uncovered_sections: Vec<RangeInclusive<i64>>, 
+}
+
 impl AOCSolutions for Day4 { // API
     fn get_star_1(input: &str) -> Result<i64, ()> {
         let optional_ranges = Day4::parse_to_rangeduos(input); 
@@ -40,38 +114,145 @@ impl AOCSolutions for Day4 { // API
     }
 }
 
-impl Day4 { // Helpers
-    fn parse_to_rangeduos(input: &str) -> Option<Vec<RangeInclDuo>> {
-        const NON_NUMERIC_MSG: &str = "[Day4::parse_to_rangeduos] Non-numeric input"; 
-
-        let optional_ranges: Vec<Option<RangeInclDuo>> = input.lines()
-            .map(|l| { // per line
-                let line: Vec<&str> = l.trim().split(['-', ',']).take(4).collect(); 
-                let nums: Option<(i64, i64, i64, i64)> = match line[..] {
-                    [str_1, str_2, str_3, str_4] => Some(( // Correctly formed
-                        str_1.parse().expect(NON_NUMERIC_MSG), 
-                        str_2.parse().expect(NON_NUMERIC_MSG), 
-                        str_3.parse().expect(NON_NUMERIC_MSG), 
-                        str_4.parse().expect(NON_NUMERIC_MSG), 
-                    )), 
-                    _ => { // Otherwise malformed
-                        eprintln!("[Day4::parse_to_rangeduos] Malformed line which contains {} < 4 parsible entries", line.len()); 
-                        None
-                    }, 
-                }; 
-
-                match nums {
-                    None => None, 
-                    Some((a, b, c, d)) => Some((a..=b, c..=d)), 
-                }
-            })
-            .collect();
-            
-        if optional_ranges.iter().any(|rd| rd.is_none()) {
-            return None;
+impl Day4 { // Analysis
+    pub fn overlap_analysis(input: &str) -> Option<OverlapAnalysis> {
+        let ranges = Day4::parse_to_rangeduos(input)?; 
+        let elves: Vec<(ElfId, RangeInclusive<i64>)> = ranges.into_iter()
+            .enumerate()
+            .flat_map(|(idx, (range_0, range_1))| [
+                (ElfId { line: idx + 1, slot: 0 }, range_0), 
+                (ElfId { line: idx + 1, slot: 1 }, range_1), 
+            ])
+            .collect(); 
+
+        let coverage = Day4::coverage_profile(elves.iter().map(|(_, range)| range)); 
+        let max_coverage = coverage.iter().map(|(_, count)| *count).max().unwrap_or(0); 
+        let sections_with = |wanted: usize| -> Vec<RangeInclusive<i64>> {
+            coverage.iter().filter(|(_, count)| *count == wanted).map(|(range, _)| range.clone()).collect()
+        }; 
+        let busiest_sections = if max_coverage == 0 { Vec::new() } else { sections_with(max_coverage) }; 
+        let uncovered_sections = sections_with(0); 
+
+        Some(OverlapAnalysis {
+            overlapping_elves: Day4::overlapping_elves(&elves), 
+            max_coverage, 
+            busiest_sections, 
+            uncovered_sections, 
+            coverage, 
+        })
+    }
+
+    /**
+    Sorts the (non-empty) ranges by start; then an elf overlaps an earlier one iff the largest end so 
+    far reaches its start, and a later one iff the next start is within its range. 
+     */
+    fn overlapping_elves(elves: &[(ElfId, RangeInclusive<i64>)]) -> Vec<ElfId> {
+        let mut by_start: Vec<&(ElfId, RangeInclusive<i64>)> = elves.iter().collect(); 
+        by_start.sort_by_key(|(_, range)| *range.start()); 
+
+        let mut overlapping: Vec<ElfId> = Vec::new(); 
+        let mut max_end: Option<i64> = None; 
+        for (idx, (elf, range)) in by_start.iter().enumerate() {
+            let overlaps_earlier = max_end.is_some_and(|end| end >= *range.start()); 
+            let overlaps_later = by_start.get(idx + 1).is_some_and(|(_, next)| next.start() <= range.end()); 
+            if overlaps_earlier || overlaps_later { overlapping.push(*elf); }
+            max_end = max_end.max(Some(*range.end())); 
+        }
+        overlapping.sort(); 
+        overlapping
+    }
+
+    /**
+    Sweep line over range boundaries: +1 before each start, -1 after each end. Boundaries are kept as 
+    `i128` so that `end + 1` cannot overflow. 
+     */
+    fn coverage_profile<'a>(ranges: impl Iterator<Item = &'a RangeInclusive<i64>>) -> Vec<(RangeInclusive<i64>, usize)> {
+        let mut events: Vec<(i128, isize)> = ranges
+            .flat_map(|range| [(*range.start() as i128, 1), (*range.end() as i128 + 1, -1)])
+            .collect(); 
+        events.sort_unstable(); 
+
+        let mut profile: Vec<(RangeInclusive<i64>, usize)> = Vec::new(); 
+        let mut count: isize = 0; 
+        let mut idx = 0; 
+        while idx < events.len() {
+            let bgn = events[idx].0; 
+            while idx < events.len() && events[idx].0 == bgn {
+                count += events[idx].1; 
+                idx += 1; 
+            }
+            let Some((end, _)) = events.get(idx) else { break; }; // Past the last end, count == 0
+            let section = (bgn as i64)..=((end - 1) as i64); 
+            match profile.last_mut() {
+                Some((last, last_count)) if *last_count == count as usize => *last = *last.start()..=*section.end(), 
+                _ => profile.push((section, count as usize)), 
+            }
+        }
+        profile
+    }
+}
+
+impl Day4 { // Parsing
+    /**
+    Parses one `<start>-<end>,<start>-<end>` pair of section assignments per line (surrounding 
+    whitespace allowed), stopping at the first malformed line. 
+     */
+    pub fn parse_assignments(input: &str, mode: ParseMode) -> Result<Vec<RangeInclDuo>, ParseError> {
+        input.lines()
+            .enumerate()
+            .map(|(idx, line)| Day4::parse_line(line.trim(), mode).map_err(|kind| ParseError { line: idx + 1, kind }))
+            .collect()
+    }
+
+    fn parse_line(mut line: &str, mode: ParseMode) -> Result<RangeInclDuo, ParseErrorKind> {
+        let range_0 = Day4::parse_range(&mut line, mode)?; 
+        Day4::parse_separator(&mut line, ',')?; 
+        let range_1 = Day4::parse_range(&mut line, mode)?; 
+        if !line.is_empty() {
+            return Err(ParseErrorKind::TrailingGarbage(line.to_string())); 
         }
+        Ok((range_0, range_1))
+    }
 
-        return Some(optional_ranges.into_iter().map(Option::unwrap).collect());             
+    fn parse_range(line: &mut &str, mode: ParseMode) -> Result<RangeInclusive<i64>, ParseErrorKind> {
+        let start = Day4::parse_section(line)?; 
+        Day4::parse_separator(line, '-')?; 
+        let end = Day4::parse_section(line)?; 
+        match mode {
+            _ if start <= end => Ok(start..=end), 
+            ParseMode::Strict => Err(ParseErrorKind::Reversed(start, end)), 
+            ParseMode::Lenient => Ok(end..=start), 
+        }
+    }
+
+    fn parse_section(line: &mut &str) -> Result<i64, ParseErrorKind> {
+        let negative = line.starts_with('-'); 
+        let sign_len = usize::from(negative); 
+        let digits_len = line[sign_len..].bytes().take_while(u8::is_ascii_digit).count(); 
+        let (token, rest) = line.split_at(sign_len + digits_len); 
+        if digits_len == 0 {
+            let found = line.split([',', '-']).next().unwrap_or_default(); 
+            return Err(ParseErrorKind::NotANumber(found.to_string())); 
+        }
+        if negative {
+            return Err(ParseErrorKind::Negative(token.to_string())); 
+        }
+        let section = token.parse().map_err(|_| ParseErrorKind::NotANumber(token.to_string()))?; 
+        *line = rest; 
+        Ok(section)
+    }
+
+    fn parse_separator(line: &mut &str, sep: char) -> Result<(), ParseErrorKind> {
+        *line = line.strip_prefix(sep).ok_or(ParseErrorKind::MissingSeparator(sep))?; 
+        Ok(())
+    }
+}
+
+impl Day4 { // Helpers
+    fn parse_to_rangeduos(input: &str) -> Option<Vec<RangeInclDuo>> {
+        Day4::parse_assignments(input, ParseMode::Strict)
+            .map_err(|e| eprintln!("[Day4::parse_to_rangeduos] Malformed input at {}", e))
+            .ok()
     }
 }
 
@@ -79,6 +260,8 @@ impl Day4 { // Helpers
 mod tests {
     use super::AOCSolutions;
     use super::Day4; 
+    use super::ElfId; 
+    use super::{ParseError, ParseErrorKind, ParseMode}; 
 
     const SAMPLE_INPUT: &str = r"2-4,6-8
         2-3,4-5
@@ -103,4 +286,48 @@ mod tests {
         assert_eq!(Day4::get_star_2(SAMPLE_INPUT).unwrap(), 4); 
         assert_eq!(Day4::get_star_2(SAMPLE_2).unwrap(), 2); 
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_overlap_analysis() {
+        let analysis = Day4::overlap_analysis("1-2,4-5\n7-8,8-9\n11-11,13-13").unwrap(); 
+        assert_eq!(analysis.overlapping_elves, vec![ElfId { line: 2, slot: 0 }, ElfId { line: 2, slot: 1 }]); 
+        assert_eq!(analysis.max_coverage, 2); 
+        assert_eq!(analysis.busiest_sections, vec![8..=8]); 
+        assert_eq!(analysis.uncovered_sections, vec![3..=3, 6..=6, 10..=10, 12..=12]); 
+        assert_eq!(analysis.coverage[..3], [(1..=2, 1), (3..=3, 0), (4..=5, 1)]); 
+    }
+
+    #[test]
+    fn test_overlap_analysis_against_brute_force() {
+        let analysis = Day4::overlap_analysis(SAMPLE_INPUT).unwrap(); 
+        let ranges = Day4::parse_to_rangeduos(SAMPLE_INPUT).unwrap(); 
+        let ranges: Vec<_> = ranges.iter().flat_map(|(r0, r1)| [r0, r1]).collect(); 
+        for (sections, count) in analysis.coverage.iter() {
+            for section in sections.clone() {
+                assert_eq!(ranges.iter().filter(|r| r.contains(&section)).count(), *count); 
+            }
+        }
+        assert_eq!(analysis.max_coverage, 8); 
+        assert_eq!(analysis.overlapping_elves.len(), 12); 
+    }
+
+    #[test]
+    fn test_parse_assignments() {
+        let strict = |input| Day4::parse_assignments(input, ParseMode::Strict).map_err(|e| e.kind); 
+        assert_eq!(strict(" 2-4,6-8 \n10-12,1-1").unwrap(), vec![(2..=4, 6..=8), (10..=12, 1..=1)]); 
+        assert_eq!(strict("2-4,9-3"), Err(ParseErrorKind::Reversed(9, 3))); 
+        assert_eq!(strict("2-4,6--8"), Err(ParseErrorKind::Negative("-8".to_string()))); 
+        assert_eq!(strict("2-4,6-8,1-2"), Err(ParseErrorKind::TrailingGarbage(",1-2".to_string()))); 
+        assert_eq!(strict("2-4,6-8 x"), Err(ParseErrorKind::TrailingGarbage(" x".to_string()))); 
+        assert_eq!(strict("2-4,a-8"), Err(ParseErrorKind::NotANumber("a".to_string()))); 
+        assert_eq!(strict("2-4 6-8"), Err(ParseErrorKind::MissingSeparator(','))); 
+        assert_eq!(strict(""), Ok(vec![])); 
+
+        let err = Day4::parse_assignments("2-4,6-8\n\n1-2,3-4", ParseMode::Strict).unwrap_err(); 
+        assert_eq!(err, ParseError { line: 2, kind: ParseErrorKind::NotANumber(String::new()) }); 
+        assert_eq!(err.to_string(), "line 2: expected a section ID, found \"\""); 
+
+        assert_eq!(Day4::parse_assignments("9-3,4-4", ParseMode::Lenient).unwrap(), vec![(3..=9, 4..=4)]); 
+        assert!(Day4::get_star_1("2-4,9-3").is_err()); 
+    }
+}
@@ -1,44 +1,320 @@
 use super::AOCSolutions; 
-use std::io::{BufRead, Seek, Cursor};
+use std::fmt;
+use std::io::{BufRead, Cursor};
 
 pub struct Day5; 
-type CargoLoad = Vec<Vec<u8>>; // FILO, literal `stacks` of crates
+pub type CargoLoad = Vec<Vec<String>>; // FILO, literal `stacks` of crate labels
+
+/**
+Whitespace-separated token of a drawing line with its character column span. 
+ */
+struct Token {
+    first_col: usize, 
+    last_col: usize, 
+    text: String, 
+}
 
-impl AOCSolutions for Day5 {
-    fn get_star_1(input: &str) -> Result<i64, ()> {
-        let mut csr = Cursor::new(input); 
-        let row_count = Day5::get_row_count(&mut csr); 
-        if row_count.is_none() {
-            return Err(()); 
+impl Token {
+    fn split(line: &str) -> Vec<Token> {
+        let mut tokens: Vec<Token> = Vec::new(); 
+        let mut current: Option<Token> = None; 
+        for (col, c) in line.trim_end().chars().enumerate() {
+            match (c.is_whitespace(), current.as_mut()) {
+                (true, _) => tokens.extend(current.take()), 
+                (false, Some(token)) => {
+                    token.last_col = col; 
+                    token.text.push(c); 
+                }, 
+                (false, None) => current = Some(Token { first_col: col, last_col: col, text: c.to_string() }), 
+            }
         }
+        tokens.extend(current); 
+        tokens
+    }
 
-        let cargo_load = Day5::construct_init_cargo_load(&mut csr, row_count.unwrap()); 
-        if cargo_load.is_none() {
-            return Err(()); 
+    /// Twice the center column, to stay in integers.
+    fn center_x2(&self) -> usize {
+        self.first_col + self.last_col
+    }
+}
+
+/**
+Crane model moving crates between stacks. A move of `count` crates is carried out as a series of 
+lifts, each taking the top crates of the source stack and putting them on the destination stack 
+without changing their order. 
+ */
+pub trait CrateMover {
+    /// Name accepted by `crate_mover_by_name`.
+    fn name(&self) -> String; 
+
+    /// Sizes of the successive lifts that move `count` crates, summing to `count`.
+    fn lifts(&self, count: usize) -> Vec<usize>; 
+
+    /**
+    Moves `count` crates from stack `from` to stack `to` (0-based). The caller guarantees that both 
+    stacks exist, differ, and that `from` holds at least `count` crates. 
+     */
+    fn move_crates(&self, cargo_load: &mut CargoLoad, count: usize, from: usize, to: usize) {
+        for lift in self.lifts(count) {
+            let split_at = cargo_load[from].len() - lift; 
+            let mut lifted = cargo_load[from].split_off(split_at); 
+            cargo_load[to].append(&mut lifted); 
         }
+    }
+}
 
-        let result = Day5::follow_instructions(&mut csr, &mut cargo_load.unwrap(), 9000); 
-        if result.is_none() {
-            return Err(()); 
+/// Moves crates one at a time.
+#[derive(Debug, Clone, Copy)]
+pub struct CrateMover9000; 
+
+impl CrateMover for CrateMover9000 {
+    fn name(&self) -> String {
+        "9000".to_string()
+    }
+
+    fn lifts(&self, count: usize) -> Vec<usize> {
+        vec![1; count]
+    }
+}
+
+/// Moves any number of crates at once.
+#[derive(Debug, Clone, Copy)]
+pub struct CrateMover9001; 
+
+impl CrateMover for CrateMover9001 {
+    fn name(&self) -> String {
+        "9001".to_string()
+    }
+
+    fn lifts(&self, count: usize) -> Vec<usize> {
+        if count == 0 { Vec::new() } else { vec![count] }
+    }
+}
+
+/// Moves up to `max_lift` crates at once, splitting larger moves into full lifts plus a remainder.
+#[derive(Debug, Clone, Copy)]
+pub struct LimitedCrateMover {
+    max_lift: usize, 
+}
+
+impl LimitedCrateMover {
+    /// Returns `None` for a `max_lift` of 0, which could never move anything.
+    pub fn new(max_lift: usize) -> Option<LimitedCrateMover> {
+        (max_lift > 0).then_some(LimitedCrateMover { max_lift })
+    }
+
+    pub fn max_lift(&self) -> usize {
+        self.max_lift
+    }
+}
+
+impl CrateMover for LimitedCrateMover {
+    fn name(&self) -> String {
+        format!("limited-{}", self.max_lift)
+    }
+
+    fn lifts(&self, count: usize) -> Vec<usize> {
+        let full = count / self.max_lift; 
+        let mut lifts = vec![self.max_lift; full]; 
+        if !count.is_multiple_of(self.max_lift) { lifts.push(count % self.max_lift); }
+        lifts
+    }
+}
+
+/**
+Instruction to move `count` crates from stack `from` to stack `to`, both 1-based as in the input. 
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    pub count: usize, 
+    pub from: usize, 
+    pub to: usize, 
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveError {
+    /// Stack (1-based) that does not exist.
+    MissingStack(usize), 
+    /// Stack (1-based) holding fewer crates than requested.
+    TooFewCrates { stack: usize, available: usize, requested: usize }, 
+    /// Move from a stack (1-based) onto itself, which `Move::apply` treats as a no-op.
+    SelfMove(usize), 
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoveError::MissingStack(stack) => write!(f, "stack {} does not exist", stack), 
+            MoveError::TooFewCrates { stack, available, requested } => 
+                write!(f, "stack {} has {} < {} crates", stack, available, requested), 
+            MoveError::SelfMove(stack) => write!(f, "moves stack {} onto itself", stack), 
         }
+    }
+}
 
-        println!("STAR 1: {}", result.unwrap()); 
-        return Ok(1); 
+impl Move {
+    /// Parses `move <count> from <from> to <to>`, ignoring surrounding whitespace.
+    pub fn parse(line: &str) -> Option<Move> {
+        match line.split_whitespace().collect::<Vec<&str>>()[..] {
+            ["move", count, "from", from, "to", to] => Some(Move {
+                count: count.parse().ok()?, 
+                from: from.parse().ok()?, 
+                to: to.parse().ok()?, 
+            }), 
+            _ => None, 
+        }
     }
 
-    fn get_star_2(input: &str) -> Result<i64, ()> {
-        let mut csr = Cursor::new(input); 
-        let row_count = Day5::get_row_count(&mut csr); 
-        if row_count.is_none() {
-            return Err(()); 
+    /// Checks that this move can be carried out on `cargo_load`.
+    pub fn check(&self, cargo_load: &CargoLoad) -> Result<(), MoveError> {
+        let stack = |n: usize| n.checked_sub(1).and_then(|idx| cargo_load.get(idx)).ok_or(MoveError::MissingStack(n)); 
+        let available = stack(self.from)?.len(); 
+        stack(self.to)?; 
+        if available < self.count {
+            return Err(MoveError::TooFewCrates { stack: self.from, available, requested: self.count }); 
         }
+        Ok(())
+    }
+
+    /// Carries out this move with `crane`, leaving `cargo_load` untouched if it cannot be carried out.
+    pub fn apply(&self, cargo_load: &mut CargoLoad, crane: &dyn CrateMover) -> Result<(), MoveError> {
+        self.check(cargo_load)?; 
+        if self.from != self.to { crane.move_crates(cargo_load, self.count, self.from - 1, self.to - 1); }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "move {} from {} to {}", self.count, self.from, self.to)
+    }
+}
+
+/**
+Instruction that failed validation. 
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidMove {
+    /// 1-based input line of the instruction.
+    pub line: usize, 
+    pub step: Move, 
+    pub error: MoveError, 
+}
+
+impl fmt::Display for InvalidMove {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: \"{}\" {}", self.line, self.step, self.error)
+    }
+}
+
+/**
+Replays a move list one instruction at a time, keeping every intermediate state so that steps can be 
+undone and the stacks inspected at any point. 
+ */
+pub struct Simulator<'a> {
+    crane: &'a dyn CrateMover, 
+    moves: Vec<Move>, 
+    /// `history[i]` is the state after the first `i` moves; only states up to the furthest step 
+    /// taken so far are recorded.
+    history: Vec<CargoLoad>, 
+    position: usize, 
+}
+
+impl<'a> Simulator<'a> {
+    pub fn new(cargo_load: CargoLoad, moves: Vec<Move>, crane: &'a dyn CrateMover) -> Simulator<'a> {
+        Simulator { crane, moves, history: vec![cargo_load], position: 0 }
+    }
+
+    /// Parses the drawing and move list of `input` up front, see `Day5::parse_input`.
+    pub fn from_input(input: &str, crane: &'a dyn CrateMover) -> Option<Simulator<'a>> {
+        let (cargo_load, moves) = Day5::parse_input(input)?; 
+        Some(Simulator::new(cargo_load, moves.into_iter().map(|(_, step)| step).collect(), crane))
+    }
+
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// Number of moves carried out to reach the current state.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position == self.moves.len()
+    }
+
+    pub fn state(&self) -> &CargoLoad {
+        &self.history[self.position]
+    }
+
+    /// States recorded so far, starting with the initial one.
+    pub fn history(&self) -> &[CargoLoad] {
+        &self.history
+    }
+
+    /**
+    Carries out the next move, returning it, or `Ok(None)` if all moves have been carried out. On 
+    error the state is left unchanged. 
+     */
+    pub fn step_forward(&mut self) -> Result<Option<Move>, MoveError> {
+        let Some(step) = self.moves.get(self.position).copied() else { return Ok(None); }; 
+        if self.history.len() == self.position + 1 {
+            let mut next = self.history[self.position].clone(); 
+            step.apply(&mut next, self.crane)?; 
+            self.history.push(next); 
+        }
+        self.position += 1; 
+        Ok(Some(step))
+    }
+
+    /// Undoes the last move, returning it, or `None` at the initial state.
+    pub fn step_back(&mut self) -> Option<Move> {
+        self.position = self.position.checked_sub(1)?; 
+        Some(self.moves[self.position])
+    }
+
+    /// Carries out all remaining moves, stopping at the first that fails along with its index.
+    pub fn run_to_end(&mut self) -> Result<(), (usize, MoveError)> {
+        while !self.is_finished() {
+            self.step_forward().map_err(|e| (self.position, e))?; 
+        }
+        Ok(())
+    }
+
+    /// Top crate of each stack, up to the first empty stack.
+    pub fn top_crates(&self) -> String {
+        self.state().iter().map_while(|stack| stack.last().map(String::as_str)).collect()
+    }
+}
+
+/**
+Looks up a crane model by name: `9000`, `9001` or `limited-<max lift>` (with a positive max lift). 
+ */
+pub fn crate_mover_by_name(name: &str) -> Option<Box<dyn CrateMover>> {
+    match name {
+        "9000" => Some(Box::new(CrateMover9000)), 
+        "9001" => Some(Box::new(CrateMover9001)), 
+        _ => name.strip_prefix("limited-")
+            .and_then(|max_lift| max_lift.parse().ok())
+            .and_then(LimitedCrateMover::new)
+            .map(|crane| Box::new(crane) as Box<dyn CrateMover>), 
+    }
+}
 
-        let cargo_load = Day5::construct_init_cargo_load(&mut csr, row_count.unwrap()); 
-        if cargo_load.is_none() {
+impl AOCSolutions for Day5 {
+    fn get_star_1(input: &str) -> Result<i64, ()> {
+        let result = Day5::top_crates(input, &CrateMover9000); 
+        if result.is_none() {
             return Err(()); 
         }
 
-        let result = Day5::follow_instructions(&mut csr, &mut cargo_load.unwrap(), 9001); 
+        println!("STAR 1: {}", result.unwrap()); 
+        return Ok(1); 
+    }
+
+    fn get_star_2(input: &str) -> Result<i64, ()> {
+        let result = Day5::top_crates(input, &CrateMover9001); 
         if result.is_none() {
             return Err(()); 
         }
@@ -51,155 +327,279 @@ impl AOCSolutions for Day5 {
 
 impl Day5 {
     /**
-    Obtains number of rows of the given `CargoLoad` environment from given `reader`, 
-    which is assumed to be at top of buffer. 
+    Returns the top crate of each stack after `crane` has followed the instructions of `input`, or 
+    `None` if `input` is malformed or its instructions cannot be followed. 
+     */
+    pub fn top_crates(input: &str, crane: &dyn CrateMover) -> Option<String> {
+        let mut simulator = Simulator::from_input(input, crane)?; 
+        if let Err((idx, e)) = simulator.run_to_end() {
+            eprintln!("[Day5::top_crates] Cannot follow instruction \"{}\": {}", simulator.moves()[idx], e); 
+            return None; 
+        }
+        Some(simulator.top_crates())
+    }
 
-    Returns `None` if EOF is at start of `reader`. Otherwise returns `Some`-wrapped number of rows 
-    and rewinds `reader` to beginning-of-file.
+    /**
+    Parses the stack drawing and the move list of `input`, pairing each move with its 1-based line 
+    number. Blank lines in the move list are skipped. Returns `None` if `input` is malformed. 
      */
-    fn get_row_count<R>(reader: &mut R) -> Option<usize> 
-        where R: BufRead + Seek {
-        let mut line = String::new();
-        let rows: usize; 
-        if let Ok(read_amnt) = reader.read_line(&mut line) {
-            match read_amnt {
-                0 => {
-                    eprintln!("[Day5::get_row_count] Malformed input: EOF at start of `reader`"); 
+    pub fn parse_input(input: &str) -> Option<(CargoLoad, Vec<(usize, Move)>)> {
+        let mut csr = Cursor::new(input); 
+        let cargo_load = Day5::parse_drawing(&mut csr)?; 
+        let moves_at = csr.position() as usize; 
+        let first_line = input[..moves_at].matches('\n').count() + 1; 
+
+        let mut moves: Vec<(usize, Move)> = Vec::new(); 
+        for (idx, line) in input[moves_at..].lines().enumerate() {
+            if line.trim().is_empty() { continue; }
+            match Move::parse(line) {
+                Some(step) => moves.push((first_line + idx, step)), 
+                None => {
+                    eprintln!("[Day5::parse_input] Malformed instruction \"{}\" at line {}", line.trim(), first_line + idx); 
                     return None; 
                 }, 
-                _ => {
-                    rows = read_amnt / 4; // inclusive of '\n'
-                    reader.rewind()
-                        .expect("[Day5::get_row_count] Cannot rewind given `reader` to beginning");  
-                }, 
             }
-        } else {
-            panic!("[Day5::get_row_count] Cannot read from given `reader`"); 
         }
+        Some((cargo_load, moves))
+    }
 
-        return Some(rows); 
+    /**
+    Validates every instruction of `input`, see `validate_moves`. Returns `None` if `input` is 
+    malformed. 
+     */
+    pub fn validate(input: &str, crane: &dyn CrateMover) -> Option<Vec<InvalidMove>> {
+        let (cargo_load, moves) = Day5::parse_input(input)?; 
+        Some(Day5::validate_moves(cargo_load, &moves, crane))
     }
 
     /**
-    Constructs `CargoLoad` environment from given `reader` (assumed at top of buffer). 
-    
-    Returns `Ok`-wrapped constructed `CargoLoad` if input is well-formed, while aligning given 
-    `reader` to the expected start of instructions. Otherwise returns `None` if input is malformed. 
+    Replays `moves` (paired with their line numbers) from `cargo_load` with `crane`, collecting every 
+    instruction that refers to a missing stack, takes more crates than its source stack holds, or 
+    moves a stack onto itself. Invalid instructions are skipped so that later ones are still checked 
+    against a meaningful state. 
      */
-    fn construct_init_cargo_load<R>(reader: &mut R, expected_row_count: usize) -> Option<CargoLoad> 
-        where R: BufRead {
-        // loop until numerics in buffer, then read past `\n` in buffer (expected to be exactly 2 lines)
-        // in the meantime, use [&u8] and split by each 4 `u8`s, trim whitespace and "[]", then add to each row if not ""
-        let mut cargo_load = vec![Vec::<u8>::new(); expected_row_count]; 
-        let expected_line_len = expected_row_count * 4; 
+    pub fn validate_moves(mut cargo_load: CargoLoad, moves: &[(usize, Move)], crane: &dyn CrateMover) -> Vec<InvalidMove> {
+        let mut invalid: Vec<InvalidMove> = Vec::new(); 
+        for &(line, step) in moves {
+            let result = step.check(&cargo_load)
+                .and(if step.from == step.to { Err(MoveError::SelfMove(step.from)) } else { Ok(()) })
+                .and_then(|_| step.apply(&mut cargo_load, crane)); 
+            if let Err(error) = result {
+                invalid.push(InvalidMove { line, step, error }); 
+            }
+        }
+        invalid
+    }
+
+    /**
+    Plans a valid move list for `crane` that turns `start` into `target`. Returns `None` if `target` 
+    is not a rearrangement of `start` (same number of stacks, same crates), or if a rearrangement 
+    needs a third stack to dig with and there are only two. 
+
+    Crates already in their target position at the bottom of a stack are never moved. The plan is 
+    built from single-crate moves, which every crane carries out the same way, then consecutive moves 
+    between the same stacks are merged wherever `crane` gives the same result in one move. The plan 
+    is not guaranteed to be the shortest possible. 
+     */
+    pub fn plan_moves(start: &CargoLoad, target: &CargoLoad, crane: &dyn CrateMover) -> Option<Vec<Move>> {
+        let mut start_crates: Vec<&String> = start.iter().flatten().collect(); 
+        let mut target_crates: Vec<&String> = target.iter().flatten().collect(); 
+        start_crates.sort_unstable(); 
+        target_crates.sort_unstable(); 
+        if start.len() != target.len() || start_crates != target_crates {
+            eprintln!("[Day5::plan_moves] Target is not a rearrangement of the start"); 
+            return None; 
+        }
+
+        let single_moves = Day5::plan_single_moves(start.clone(), target)?; 
+
+        // Merge runs of moves between the same stacks when the crane agrees
+        let mut plan: Vec<Move> = Vec::new(); 
+        let mut state = start.clone(); 
+        let mut plan_state = start.clone(); // State before the last planned move
+        for step in single_moves {
+            step.apply(&mut state, crane).ok()?; 
+            if let Some(last) = plan.last_mut().filter(|last| (last.from, last.to) == (step.from, step.to)) {
+                let merged = Move { count: last.count + 1, ..*last }; 
+                let mut merged_state = plan_state.clone(); 
+                if merged.apply(&mut merged_state, crane).is_ok() && merged_state == state {
+                    *last = merged; 
+                    continue; 
+                }
+            }
+            if let Some(last) = plan.last() { last.apply(&mut plan_state, crane).ok()?; }
+            plan.push(step); 
+        }
+        Some(plan)
+    }
+
+    /**
+    Single-crate moves turning `state` into `target`. A stack is "clean" if it holds only crates in 
+    their target position, i.e., a prefix of its target. Repeatedly: 
+    1. If a clean, incomplete stack needs a crate lying on top of another stack, move it there. 
+    2. Otherwise, if there is a clean, incomplete stack, dig out the crate it needs next by moving the 
+       crates above it to a third stack. 
+    3. Otherwise, clean the incomplete stack with the fewest misplaced crates by moving its top 
+       elsewhere. 
+
+    Unwanted crates go to dirty stacks where possible. Crates in their target position are never 
+    moved, and every run of steps 2 and 3 ends in step 1, which places a crate, so this terminates. 
+     */
+    fn plan_single_moves(mut state: CargoLoad, target: &CargoLoad) -> Option<Vec<Move>> {
+        let settled = |state: &CargoLoad, idx: usize| state[idx].iter().zip(target[idx].iter())
+            .take_while(|(current, wanted)| current == wanted)
+            .count(); 
+        let mut moves: Vec<Move> = Vec::new(); 
+        let mut push_move = |state: &mut CargoLoad, from: usize, to: usize| {
+            let moved = state[from].pop().unwrap(); 
+            state[to].push(moved); 
+            moves.push(Move { count: 1, from: from + 1, to: to + 1 }); 
+        }; 
 
         loop {
-            let mut line = String::new(); 
-            if let Ok(read_amnt) = reader.read_line(&mut line) {
-                match read_amnt {
-                    0 => { // EOF
-                        eprintln!("[Day5::construct_init_cargo_load] Malformed input: EOF at start of `reader`"); 
+            let stack_count = state.len(); 
+            let misplaced: Vec<usize> = (0..stack_count).map(|idx| state[idx].len() - settled(&state, idx)).collect(); 
+            let clean_incomplete = (0..stack_count)
+                .find(|idx| misplaced[*idx] == 0 && state[*idx].len() < target[*idx].len()); 
+
+            match clean_incomplete {
+                Some(to) => {
+                    let wanted = &target[to][state[to].len()]; 
+                    let on_top = (0..stack_count)
+                        .find(|idx| misplaced[*idx] > 0 && state[*idx].last() == Some(wanted)); 
+                    if let Some(from) = on_top {
+                        push_move(&mut state, from, to); 
+                        continue; 
+                    }
+
+                    // The wanted crate is buried in the misplaced part of another stack
+                    let from = (0..stack_count)
+                        .find(|idx| state[*idx][state[*idx].len() - misplaced[*idx]..].contains(wanted))?; 
+                    let Some(spare) = Day5::pick_dump(&misplaced, &[to, from]) else {
+                        eprintln!("[Day5::plan_moves] Cannot dig for crate \"{}\" with only two stacks", wanted); 
                         return None; 
-                    }, 
-                    read_amnt if read_amnt == expected_line_len => { // Crate or row count line
-                        if line.as_bytes().iter().any(u8::is_ascii_digit) { // row count line
-                            continue; 
-                        }
-
-                        for (idx, chunk) in line.as_bytes().chunks(4).enumerate() {
-                            let mut chunk_iter = chunk.iter().filter(|u| u.is_ascii_alphabetic() ); 
-                            if let Some(item) = chunk_iter.next() { // Has element, assumed to be 1 element
-                                cargo_load[idx].push(item.clone()); 
-                            } // else no element, do nothing
-                        }
-                    }, 
-                    _ => { // Assumed "\n", maybe invalid but whatever -- expected behavior
-                        cargo_load.iter_mut().for_each(|v| v.reverse()); // Should have used `Deque`
-                        // Already aligned to instructions if well-formed
-                        return Some(cargo_load); 
-                    }, 
-                }
-            } else {
-                panic!("[Day5::construct_init_cargo_load] Cannot read from given `reader`"); 
+                    }; 
+                    push_move(&mut state, from, spare); 
+                }, 
+                None => {
+                    // Some stack holds fewer crates than it should, so it must be dirty and incomplete
+                    let Some(from) = (0..stack_count)
+                        .filter(|idx| misplaced[*idx] > 0 && settled(&state, *idx) < target[*idx].len())
+                        .min_by_key(|idx| misplaced[*idx]) else {
+                        return Some(moves); // Every stack is clean and complete
+                    }; 
+                    let to = Day5::pick_dump(&misplaced, &[from])?; 
+                    push_move(&mut state, from, to); 
+                }, 
             }
         }
     }
 
+    /// Stack to put unwanted crates on, preferring one that is dirty already, other than `excluded`.
+    fn pick_dump(misplaced: &[usize], excluded: &[usize]) -> Option<usize> {
+        let candidates = (0..misplaced.len()).filter(|idx| !excluded.contains(idx)); 
+        candidates.clone().find(|idx| misplaced[*idx] > 0).or(candidates.min())
+    }
+
     /**
-    Alters given `cargo_load` in accordance to instructions provided in `reader`. 
+    Constructs `CargoLoad` environment from the stack drawing at the top of `reader`, leaving 
+    `reader` at the expected start of instructions (past the blank line after the drawing). 
 
-    Returns `Some`-wrapped `String` containing top crates of each row (i.e., numbered column) after 
-    performing instructions. Otherwise returns `None` if given instructions cannot be followed. 
+    Stacks are located by the numbered footer line; each `[label]` crate belongs to the stack whose 
+    footer number is horizontally closest to it. Lines may be ragged (e.g., with trailing spaces 
+    stripped) and labels may be any non-empty text without whitespace or brackets. 
 
-    > **This operation is destructive** -- returned results are popped from `cargo_load` sub-vectors.  
+    Returns `None` if the drawing is malformed. 
      */
-    fn follow_instructions<R>(reader: &mut R, cargo_load: &mut CargoLoad, model: usize) -> Option<String> 
+    pub fn parse_drawing<R>(reader: &mut R) -> Option<CargoLoad> 
         where R: BufRead {
-        loop {
+        let mut crate_lines: Vec<Vec<Token>> = Vec::new(); 
+        let footer = loop {
             let mut line = String::new(); 
-            if let Ok(read_amnt) = reader.read_line(&mut line) {
-                match read_amnt {
-                    0 => { // EOF
-                        let top_crates: Vec<u8> = cargo_load.iter_mut()
-                            .map_while(|r| r.pop())
-                            .collect(); 
-                        if let Ok(s) = String::from_utf8(top_crates) {
-                            return Some(s); 
-                        } else {
-                            panic!("[Day5::follow_instructions] Invalid `cargo_load` elements -- non-UTF8 encountered"); 
-                        }
+            if reader.read_line(&mut line).expect("[Day5::parse_drawing] Cannot read from given `reader`") == 0 {
+                eprintln!("[Day5::parse_drawing] Malformed input: EOF before numbered footer line"); 
+                return None; 
+            }
+            let tokens = Token::split(&line); 
+            if tokens.is_empty() {
+                eprintln!("[Day5::parse_drawing] Malformed input: blank line before numbered footer line"); 
+                return None; 
+            }
+            if tokens.iter().all(|t| t.text.parse::<usize>().is_ok()) {
+                break tokens; 
+            }
+            crate_lines.push(tokens); 
+        }; 
+
+        let mut blank = String::new(); 
+        reader.read_line(&mut blank).expect("[Day5::parse_drawing] Cannot read from given `reader`"); 
+        if !blank.trim().is_empty() {
+            eprintln!("[Day5::parse_drawing] Malformed input: expected blank line after footer, found \"{}\"", blank.trim()); 
+            return None; 
+        }
+
+        // Bottom-up, so that each stack is pushed in order
+        let mut cargo_load: CargoLoad = vec![Vec::new(); footer.len()]; 
+        for tokens in crate_lines.iter().rev() {
+            let mut seen = vec![false; footer.len()]; 
+            for token in tokens {
+                let label = match token.text.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+                    Some(label) if !label.is_empty() && !label.contains(['[', ']']) => label, 
+                    _ => {
+                        eprintln!("[Day5::parse_drawing] Malformed input: \"{}\" is not a crate", token.text); 
+                        return None; 
                     }, 
-                    _ => { // Instruction
-                        // Parse into config
-                        let config: Vec<usize> = line.trim().split(' ')
-                            .filter_map(|sp| 
-                                if let Ok(n) = sp.parse::<usize>() { Some(n) } else { None } 
-                            )
-                            .collect(); 
-                        if config.len() != 3 {
-                            panic!("[Day5::follow_instructions] Undefined or malformed instruction \"{}\"", line); 
-                        }
-
-                        // Check existence of rows, obtain mut ref for from row (necessary?)
-                        let crate_count = config[0]; 
-                        let from = config[1] - 1;
-                        let to = config[2] - 1; 
-                        if from == to { continue; }
-                        if to > cargo_load.len() {
-                            eprintln!("[Day5::follow_instructions] Cannot follow instruction \"{}\": row {} does not exist", line.trim(), to);
-                            return None;  
-                        }
-                        let from_row: &mut Vec<u8>; 
-                        match cargo_load.get_mut(from) { 
-                            Some(r) => from_row = r, 
-                            None => {
-                                eprintln!("[Day5::follow_instructions] Cannot follow instruction \"{}\": row {} does not exist", line.trim(), from); 
-                                return None; 
-                            }, 
-                        }
-                        // Check from row has enough crates
-                        if from_row.len() < crate_count {
-                            eprintln!(
-                                "[Day5::follow_instructions] Cannot follow instruction \"{}\": row {} has {} < {} crates", 
-                                line.trim(), from, from_row.len(), crate_count
-                            );
-                            return None; 
-                        }
-
-                        // Clone-and-truncate-and-append (maybe better solution in API?)
-                        let mut to_be_moved: Vec<u8> = Vec::with_capacity(crate_count); 
-                        from_row[from_row.len() - crate_count..].clone_into(&mut to_be_moved); 
-                        match model {
-                            9000 => to_be_moved.reverse(),  // "[moved] one at a time"
-                            9001 => (), // "move multiple crates at once"
-                            _ => panic!("[Day5::follow_instructions] Undefined model number: CrateMover {}", model), 
-                        }
-                        from_row.truncate(from_row.len() - crate_count); 
-                        cargo_load[to].append(&mut to_be_moved); 
-                    }
+                }; 
+                let stack = (0..footer.len())
+                    .min_by_key(|idx| footer[*idx].center_x2().abs_diff(token.center_x2()))
+                    .unwrap(); 
+                if std::mem::replace(&mut seen[stack], true) {
+                    eprintln!("[Day5::parse_drawing] Malformed input: two crates above stack {} on one line", footer[stack].text); 
+                    return None; 
                 }
+                cargo_load[stack].push(label.to_string()); 
             }
         }
+        Some(cargo_load)
+    }
+
+    /**
+    Renders `cargo_load` in the puzzle's drawing format, i.e., the inverse of `parse_drawing`: one 
+    line per crate level from the top down, then the numbered footer, each line ending in `\n`. 
+
+    Columns are as wide as their widest crate (at least as wide as their number) and separated by 
+    one space; crates and numbers are centered in their column, and lines are padded to full width. 
+     */
+    pub fn render_drawing(cargo_load: &CargoLoad) -> String {
+        let numbers: Vec<String> = (1..=cargo_load.len()).map(|n| n.to_string()).collect(); 
+        let widths: Vec<usize> = cargo_load.iter().zip(numbers.iter())
+            .map(|(stack, number)| stack.iter()
+                .map(|label| label.chars().count() + 2)
+                .max()
+                .unwrap_or(3)
+                .max(number.len()))
+            .collect(); 
+        let height = cargo_load.iter().map(Vec::len).max().unwrap_or(0); 
+
+        let render_line = |cells: Vec<Option<String>>| -> String {
+            let padded: Vec<String> = cells.into_iter().zip(widths.iter())
+                .map(|(cell, width)| {
+                    let cell = cell.unwrap_or_default(); 
+                    let left = (width - cell.chars().count()) / 2; 
+                    format!("{}{:<rest$}", " ".repeat(left), cell, rest = width - left)
+                })
+                .collect(); 
+            padded.join(" ") + "\n"
+        }; 
+
+        let mut drawing = String::new(); 
+        for level in (0..height).rev() {
+            drawing += &render_line(cargo_load.iter()
+                .map(|stack| stack.get(level).map(|label| format!("[{}]", label)))
+                .collect()); 
+        }
+        drawing += &render_line(numbers.into_iter().map(Some).collect()); 
+        drawing
     }
 }
 
@@ -207,6 +607,8 @@ impl Day5 {
 mod tests {
     use super::Day5; 
     use super::AOCSolutions; 
+    use super::{crate_mover_by_name, CrateMover, CrateMover9000, CrateMover9001, LimitedCrateMover}; 
+    use super::{CargoLoad, InvalidMove, Move, MoveError, Simulator}; 
     use std::io::Cursor; 
 
     const SAMPLE_INPUT: &str = r"    [D]    
@@ -219,50 +621,53 @@ move 3 from 1 to 3
 move 2 from 2 to 1
 move 1 from 1 to 2";
 
+    /// Stack of single-character crates, bottom first.
+    fn stack(crates: &str) -> Vec<String> {
+        crates.chars().map(String::from).collect()
+    }
+
     #[test]
     fn test_get_result_9000() {
         let mut br = Cursor::new(SAMPLE_INPUT);
 
-        // get number of columns in representation
-        let row_count = Day5::get_row_count(&mut br).unwrap(); 
-        assert_eq!(row_count, 3); 
-
         // construct init cargo load (hold)
-        let mut cargo_load = Day5::construct_init_cargo_load(&mut br, row_count).unwrap(); 
-        assert_eq!(cargo_load[0], "ZN".as_bytes()); 
-        assert_eq!(cargo_load[1], "MCD".as_bytes()); 
-        assert_eq!(cargo_load[2], "P".as_bytes()); 
+        let cargo_load = Day5::parse_drawing(&mut br).unwrap(); 
+        assert_eq!(cargo_load.len(), 3); 
+        assert_eq!(cargo_load[0], stack("ZN")); 
+        assert_eq!(cargo_load[1], stack("MCD")); 
+        assert_eq!(cargo_load[2], stack("P")); 
         
         // follow instructions
-        let result = Day5::follow_instructions(&mut br, &mut cargo_load, 9000).unwrap(); 
-        assert_eq!(result.as_str(), "CMZ");
-        // Since destructive... 
-        assert_eq!(cargo_load[0], "".as_bytes()); 
-        assert_eq!(cargo_load[1], "".as_bytes()); 
-        assert_eq!(cargo_load[2], "PDN".as_bytes()); 
+        let moves = Day5::parse_input(SAMPLE_INPUT).unwrap().1.into_iter().map(|(_, step)| step).collect(); 
+        let mut simulator = Simulator::new(cargo_load, moves, &CrateMover9000); 
+        simulator.run_to_end().unwrap(); 
+        assert_eq!(simulator.top_crates(), "CMZ");
+        // Not destructive
+        assert_eq!(simulator.state()[0], stack("C")); 
+        assert_eq!(simulator.state()[1], stack("M")); 
+        assert_eq!(simulator.state()[2], stack("PDNZ")); 
     }
 
     #[test]
     fn test_get_result_9001() {
         let mut br = Cursor::new(SAMPLE_INPUT);
 
-        // get number of columns in representation
-        let row_count = Day5::get_row_count(&mut br).unwrap(); 
-        assert_eq!(row_count, 3); 
-
         // construct init cargo load (hold)
-        let mut cargo_load = Day5::construct_init_cargo_load(&mut br, row_count).unwrap(); 
-        assert_eq!(cargo_load[0], "ZN".as_bytes()); 
-        assert_eq!(cargo_load[1], "MCD".as_bytes()); 
-        assert_eq!(cargo_load[2], "P".as_bytes()); 
+        let cargo_load = Day5::parse_drawing(&mut br).unwrap(); 
+        assert_eq!(cargo_load.len(), 3); 
+        assert_eq!(cargo_load[0], stack("ZN")); 
+        assert_eq!(cargo_load[1], stack("MCD")); 
+        assert_eq!(cargo_load[2], stack("P")); 
         
         // follow instructions
-        let result = Day5::follow_instructions(&mut br, &mut cargo_load, 9001).unwrap(); 
-        assert_eq!(result.as_str(), "MCD");
-        // Since destructive... 
-        assert_eq!(cargo_load[0], "".as_bytes()); 
-        assert_eq!(cargo_load[1], "".as_bytes()); 
-        assert_eq!(cargo_load[2], "PZN".as_bytes()); 
+        let moves = Day5::parse_input(SAMPLE_INPUT).unwrap().1.into_iter().map(|(_, step)| step).collect(); 
+        let mut simulator = Simulator::new(cargo_load, moves, &CrateMover9001); 
+        simulator.run_to_end().unwrap(); 
+        assert_eq!(simulator.top_crates(), "MCD");
+        // Not destructive
+        assert_eq!(simulator.state()[0], stack("M")); 
+        assert_eq!(simulator.state()[1], stack("C")); 
+        assert_eq!(simulator.state()[2], stack("PZND")); 
     }
 
     #[test]
@@ -274,4 +679,131 @@ move 1 from 1 to 2";
     fn test_get_star_2() {
         assert_eq!(Day5::get_star_2(SAMPLE_INPUT).unwrap(), 1); 
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_limited_crate_mover() {
+        assert!(LimitedCrateMover::new(0).is_none()); 
+        let crane = LimitedCrateMover::new(2).unwrap(); 
+        assert_eq!(crane.max_lift(), 2); 
+        assert_eq!(crane.lifts(5), vec![2, 2, 1]); 
+        let mut cargo_load: CargoLoad = vec![stack("ABCDE"), Vec::new()]; 
+        crane.move_crates(&mut cargo_load, 5, 0, 1); 
+        assert_eq!(cargo_load[1], stack("DEBCA")); 
+
+        // Lifting one crate at a time is the CrateMover 9000, lifting everything is the 9001
+        assert_eq!(Day5::top_crates(SAMPLE_INPUT, &LimitedCrateMover::new(1).unwrap()).unwrap(), "CMZ"); 
+        assert_eq!(Day5::top_crates(SAMPLE_INPUT, &LimitedCrateMover::new(3).unwrap()).unwrap(), "MCD"); 
+    }
+
+    #[test]
+    fn test_crate_mover_by_name() {
+        assert_eq!(crate_mover_by_name("9000").unwrap().name(), "9000"); 
+        assert_eq!(crate_mover_by_name("9001").unwrap().name(), "9001"); 
+        assert_eq!(crate_mover_by_name("limited-4").unwrap().lifts(9), vec![4, 4, 1]); 
+        assert!(crate_mover_by_name("limited-0").is_none()); 
+        assert!(crate_mover_by_name("9002").is_none()); 
+    }
+
+    #[test]
+    fn test_parse_drawing_wide_and_ragged() {
+        // Trailing spaces stripped, eleven stacks, multi-character labels
+        let drawing = "[A]                                    [K]\n[B]     [CC]                        [J] [L]\n 1   2   3   4   5   6   7   8   9   10  11\n\nmove 1 from 1 to 2"; 
+        let mut br = Cursor::new(drawing); 
+        let cargo_load = Day5::parse_drawing(&mut br).unwrap(); 
+        assert_eq!(cargo_load.len(), 11); 
+        assert_eq!(cargo_load[0], stack("BA")); 
+        assert_eq!(cargo_load[2], vec!["CC".to_string()]); 
+        assert_eq!(cargo_load[9], stack("J")); 
+        assert_eq!(cargo_load[10], stack("LK")); 
+        assert_eq!(Day5::top_crates(drawing, &CrateMover9000).unwrap(), "BACC"); // Stops at empty stack 4
+
+        assert!(Day5::parse_drawing(&mut Cursor::new("[A] x\n 1  2\n\n")).is_none()); 
+        assert!(Day5::parse_drawing(&mut Cursor::new("[A]\n[B]\n")).is_none()); 
+    }
+
+    #[test]
+    fn test_render_drawing_round_trip() {
+        let drawing_len = SAMPLE_INPUT.find("\n\n").unwrap() + 1; 
+        let cargo_load = Day5::parse_drawing(&mut Cursor::new(SAMPLE_INPUT)).unwrap(); 
+        assert_eq!(Day5::render_drawing(&cargo_load), &SAMPLE_INPUT[..drawing_len]); 
+
+        let wide: CargoLoad = vec![stack("AB"), vec!["LONG".to_string()], Vec::new(), stack("C")]; 
+        let rendered = Day5::render_drawing(&wide); 
+        assert_eq!(rendered, "[B]               \n[A] [LONG]     [C]\n 1    2     3   4 \n"); 
+        assert_eq!(Day5::parse_drawing(&mut Cursor::new(rendered + "\n")).unwrap(), wide); 
+    }
+
+    #[test]
+    fn test_simulator_steps() {
+        let mut simulator = Simulator::from_input(SAMPLE_INPUT, &CrateMover9000).unwrap(); 
+        assert_eq!(simulator.moves()[1], Move { count: 3, from: 1, to: 3 }); 
+        assert_eq!(simulator.top_crates(), "NDP"); 
+        assert_eq!(simulator.step_back(), None); 
+
+        assert_eq!(simulator.step_forward(), Ok(Some(Move { count: 1, from: 2, to: 1 }))); 
+        assert_eq!(simulator.top_crates(), "DCP"); 
+        simulator.step_forward().unwrap(); 
+        assert_eq!(simulator.step_back(), Some(Move { count: 3, from: 1, to: 3 })); 
+        assert_eq!(simulator.position(), 1); 
+        assert_eq!(simulator.top_crates(), "DCP"); 
+        assert_eq!(simulator.history().len(), 3); 
+
+        simulator.run_to_end().unwrap(); 
+        assert!(simulator.is_finished()); 
+        assert_eq!(simulator.step_forward(), Ok(None)); 
+        assert_eq!(simulator.history().len(), 5); 
+        assert_eq!(Day5::render_drawing(&simulator.history()[0]), Day5::render_drawing(&Day5::parse_drawing(&mut Cursor::new(SAMPLE_INPUT)).unwrap())); 
+    }
+
+    #[test]
+    fn test_simulator_rejects_impossible_move() {
+        let input = SAMPLE_INPUT.replace("move 2 from 2 to 1", "move 5 from 2 to 1"); 
+        let mut simulator = Simulator::from_input(&input, &CrateMover9000).unwrap(); 
+        assert_eq!(simulator.run_to_end(), Err((2, MoveError::TooFewCrates { stack: 2, available: 2, requested: 5 }))); 
+        assert_eq!(simulator.position(), 2); 
+        assert!(Day5::top_crates(&input, &CrateMover9000).is_none()); 
+        assert_eq!(Day5::parse_input(SAMPLE_INPUT).unwrap().1[0].0, 6); 
+    }
+
+    #[test]
+    fn test_validate_moves() {
+        let input = SAMPLE_INPUT.to_string() + "\nmove 1 from 4 to 1\nmove 9 from 3 to 1\nmove 1 from 3 to 3\nmove 1 from 3 to 1"; 
+        let invalid = Day5::validate(&input, &CrateMover9000).unwrap(); 
+        assert_eq!(invalid, vec![
+            InvalidMove { line: 10, step: Move { count: 1, from: 4, to: 1 }, error: MoveError::MissingStack(4) }, 
+            InvalidMove { line: 11, step: Move { count: 9, from: 3, to: 1 }, error: MoveError::TooFewCrates { stack: 3, available: 4, requested: 9 } }, 
+            InvalidMove { line: 12, step: Move { count: 1, from: 3, to: 3 }, error: MoveError::SelfMove(3) }, 
+        ]); 
+        assert_eq!(invalid[0].to_string(), "line 10: \"move 1 from 4 to 1\" stack 4 does not exist"); 
+        assert!(Day5::validate(SAMPLE_INPUT, &CrateMover9001).unwrap().is_empty()); 
+    }
+
+    #[test]
+    fn test_plan_moves() {
+        let start = Day5::parse_drawing(&mut Cursor::new(SAMPLE_INPUT)).unwrap(); 
+        let targets: Vec<CargoLoad> = vec![
+            start.clone(), 
+            vec![stack("PDNZ"), stack("M"), stack("C")], 
+            vec![stack("ZNCM"), stack(""), stack("DP")], 
+            vec![stack(""), stack("PMCDNZ"), stack("")], 
+        ]; 
+        let cranes: [&dyn CrateMover; 3] = [&CrateMover9000, &CrateMover9001, &LimitedCrateMover::new(2).unwrap()]; 
+        for target in targets.iter() {
+            for crane in cranes {
+                let plan = Day5::plan_moves(&start, target, crane).unwrap(); 
+                let mut simulator = Simulator::new(start.clone(), plan.clone(), crane); 
+                simulator.run_to_end().unwrap(); 
+                assert_eq!(simulator.state(), target); 
+                let numbered: Vec<(usize, Move)> = plan.into_iter().enumerate().collect(); 
+                assert!(Day5::validate_moves(start.clone(), &numbered, crane).is_empty()); 
+            }
+        }
+        // Z and N stay at the bottom of stack 1 throughout
+        let plan = Day5::plan_moves(&start, &targets[2], &CrateMover9000).unwrap(); 
+        let mut simulator = Simulator::new(start.clone(), plan, &CrateMover9000); 
+        simulator.run_to_end().unwrap(); 
+        assert!(simulator.history().iter().all(|state| state[0].starts_with(&stack("ZN")))); 
+        assert!(Day5::plan_moves(&start, &start, &CrateMover9000).unwrap().is_empty()); 
+        assert!(Day5::plan_moves(&start, &vec![stack("ZN"), stack("MCD"), stack("X")], &CrateMover9000).is_none()); 
+    }
+}
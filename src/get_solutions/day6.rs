@@ -1,139 +1,221 @@
-// HANDICAP: Use parallelization (thread pool seems easy and most applicable?)
-// It would be dumb in this case to use a "sliding window" approach -- at least not a trivial one
-// Need to keep bookmark on done windows (e.g., idx = 1...4), this way threads can perform 
-// non-ordered processing. 
+// Both stars use `Day6::find_marker`, an O(n) sliding window over a last-seen table per byte value.
+// `MarkerStream` applies the same window to any `Read`, for several window sizes in one pass. 
 
-// Questions seems to imply single-thread processing (*first* marker), but... suffices to use a min-
-// heuristic when threads pick their tasks to instantly return when all prev per-4-idxs are NOT in 
-// available tasks! e.g., when one start marker is found, shrink the job pool to < that start marker.
+// `PooledDecoder` (`Day6::pooled_decode`) is the original thread-pool approach: batches of window 
+// starts are checked in parallel and joined in order, so the first batch with a marker has the 
+// earliest one. It is kept for comparison, see `benches/day6.rs`. 
 
 use super::AOCSolutions; 
-use std::collections::{VecDeque, HashSet};
-use std::sync::{mpsc, Arc, Mutex};
-use std::thread;
-use std::time::Duration;  
+use crate::pool::{JobHandle, WorkerPool}; 
+use crate::runner; 
+use std::collections::{HashSet, VecDeque};
+use std::io::{self, Read};
+use std::sync::Arc;
 
 pub struct Day6; 
 struct PooledDecoder {
-    data: Vec<u8>, 
-    available_jobs: VecDeque<usize>, 
+    data: Arc<[u8]>, 
     config: usize, 
-    decoders: Vec<Decoder>, 
-    job_tx: Option<mpsc::Sender<(usize, Vec<u8>)>>,  // Send u8 subslice to individual decoder threads
-    bound_rx: mpsc::Receiver<usize>, // Receive results from threads to alter u8 slice range
+    pool: WorkerPool, 
 }
 
 impl PooledDecoder {
+    /// Window starts checked per pool job.
+    const BATCH_LEN: usize = 1024; 
+    /// Jobs queued per pool thread at any time, see `execute`.
+    const BATCHES_PER_THREAD: usize = 2; 
+
     pub fn new(data: Vec<u8>, config: usize, thread_count: usize) -> PooledDecoder {
-        assert!(thread_count > 0); 
         assert!(config > 0); 
         assert!(data.len() >= config);
         
-        let idx_bgn_candidates: VecDeque<usize> = (0..=(data.len() - config)).collect(); // Sorted
-
-        let (job_tx, job_rx) = mpsc::channel::<(usize, Vec<u8>)>(); 
-        let (bound_tx, bound_rx) = mpsc::channel::<usize>(); 
-        let job_rx = Arc::new(Mutex::new(job_rx)); 
-
-        let mut decoders = Vec::with_capacity(thread_count); 
-        for id in 0..thread_count {
-            let bound_tx = bound_tx.clone(); 
-            decoders.push(Decoder::new(id, bound_tx, Arc::clone(&job_rx))); 
-        }
-
-        return PooledDecoder{
-            data, 
-            available_jobs: idx_bgn_candidates, 
-            config, 
-            decoders, 
-            job_tx: Some(job_tx),  
-            bound_rx, 
-        }; 
+        PooledDecoder { data: data.into(), config, pool: WorkerPool::new(thread_count) }
     }
 
+    /**
+    Checks batches of window starts on the pool, keeping at most `BATCHES_PER_THREAD` batches per 
+    thread queued ahead of the one being joined, so that an early return (marker found, or the run 
+    cancelled) only has to wait for those when the pool is dropped. 
+     */
     pub fn execute(&mut self) -> Option<usize> {
-        let mut best: Option<usize> = None;
-        // Send jobs
-        while !self.available_jobs.is_empty() { 
-            let job = self.available_jobs.pop_front().unwrap();
-            self.job_tx.as_ref().unwrap().send((job, self.data[job..(job + self.config)].to_vec()))
-                .unwrap(); 
-        }
-
-        // Receive results
+        // Batches are submitted and joined in order, so the first batch with a marker has the earliest
+        let candidate_count = self.data.len() - self.config + 1; 
+        let mut batch_starts = (0..candidate_count).step_by(Self::BATCH_LEN); 
+        let mut in_flight: VecDeque<JobHandle<Option<usize>>> = VecDeque::new(); 
         loop {
-            match self.bound_rx.recv_timeout(Duration::from_secs(1)) {
-                Ok(new_bound) => {
-                    let new_bound = new_bound + self.config; // align to rest of substring
-                    match best {
-                        None => best = Some(new_bound), 
-                        Some(v) => if v > new_bound { best = Some(new_bound) }, 
-                    }
-                }, 
-                Err(mpsc::RecvTimeoutError::Disconnected) => {
-                    panic!("[day6::PooledDecoder::execute] Unexpected `mpsc` receiver closure from `Decoder` client")
-                }, 
-                _ => break, 
+            while in_flight.len() < self.pool.thread_count() * Self::BATCHES_PER_THREAD {
+                let Some(bgn) = batch_starts.next() else { break; }; 
+                let (data, config) = (Arc::clone(&self.data), self.config); 
+                let end = (bgn + Self::BATCH_LEN).min(candidate_count); 
+                in_flight.push_back(self.pool.submit(move || (bgn..end).find(|idx| Day6::is_packet_start_marker(&data[*idx..(*idx + config)])))); 
+            }
+
+            // Receive results
+            let handle = in_flight.pop_front()?; 
+            if runner::is_cancelled() {
+                eprintln!("[day6::PooledDecoder::execute] Cancelled while receiving results"); 
+                return None; 
+            }
+            let found = handle.join()
+                .expect("[day6::PooledDecoder::execute] Decoder job panicked"); 
+            if let Some(idx) = found {
+                return Some(idx + self.config); // align to rest of substring
             }
         }
-        return best; 
     }
 }
 
-impl Drop for PooledDecoder {
-    fn drop(&mut self) {
-        drop(self.job_tx.take()); 
+/**
+Window of `window` pairwise distinct bytes ending after the first `end` bytes of a stream. 
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Marker {
+    pub window: usize, 
+    pub end: usize, 
+}
 
-        for decoder in &mut self.decoders {
-            if let Some(handle) = decoder.handle.take() {
-                handle.join().unwrap(); 
-                eprintln!("[day6::PooledDecoder::drop] Decoder disconnected"); 
-            }
+/**
+Iterator over every marker of a datastream read from `R`, for several window sizes in one pass, 
+ordered by `end` and then by window size. 
+
+Uses the same last-seen table as `Day6::find_marker`, so memory use is bounded by the read buffer 
+regardless of the stream length. After a read error has been yielded, the iterator is exhausted. 
+ */
+pub struct MarkerStream<R: Read> {
+    reader: R, 
+    buf: Box<[u8]>, 
+    buf_pos: usize, 
+    buf_len: usize, 
+    /// Distinct, non-zero window sizes in ascending order.
+    windows: Vec<usize>, 
+    last_seen: [Option<usize>; 256], 
+    /// Bytes consumed so far.
+    position: usize, 
+    /// Start of the longest run of distinct bytes ending at `position`.
+    start: usize, 
+    pending: VecDeque<Marker>, 
+    finished: bool, 
+}
+
+impl<R: Read> MarkerStream<R> {
+    const BUF_LEN: usize = 8 * 1024; 
+
+    /// Markers for each of `windows`; duplicate and zero sizes are ignored.
+    pub fn new(reader: R, windows: &[usize]) -> MarkerStream<R> {
+        let mut windows: Vec<usize> = windows.iter().copied().filter(|w| *w > 0).collect(); 
+        windows.sort_unstable(); 
+        windows.dedup(); 
+        MarkerStream {
+            reader, 
+            buf: vec![0; Self::BUF_LEN].into_boxed_slice(), 
+            buf_pos: 0, 
+            buf_len: 0, 
+            windows, 
+            last_seen: [None; 256], 
+            position: 0, 
+            start: 0, 
+            pending: VecDeque::new(), 
+            finished: false, 
         }
     }
 }
-struct Decoder {
-    id: usize, 
-    handle: Option<thread::JoinHandle<()>>, 
-}
 
-impl Decoder {
-    fn new (id: usize, bound_tx: mpsc::Sender<usize>, job_rx: Arc<Mutex<mpsc::Receiver<(usize, Vec<u8>)>>>) -> Decoder {
-        let handle = thread::spawn(move || 
-            loop {
-                match job_rx.lock().unwrap().recv() { 
-                    Ok((idx, u8_subvec)) => {
-                        if Day6::is_packet_start_marker(&u8_subvec) {
-                            bound_tx.send(idx).unwrap();  
-                        }
+impl<R: Read> Iterator for MarkerStream<R> {
+    type Item = io::Result<Marker>; 
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(marker) = self.pending.pop_front() { return Some(Ok(marker)); }
+            if self.finished { return None; }
+
+            if self.buf_pos == self.buf_len {
+                match self.reader.read(&mut self.buf) {
+                    Ok(0) => self.finished = true, 
+                    Ok(read_amnt) => (self.buf_pos, self.buf_len) = (0, read_amnt), 
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => (), 
+                    Err(e) => {
+                        self.finished = true; 
+                        return Some(Err(e)); 
                     }, 
-                    Err(_) => break, // PooledDecoder deconnection  
                 }
+                continue; 
             }
-        ); 
-        
-        return Decoder{id, handle: Some(handle)}; 
+
+            let u = self.buf[self.buf_pos] as usize; 
+            self.buf_pos += 1; 
+            if let Some(prev) = self.last_seen[u].filter(|prev| *prev >= self.start) {
+                self.start = prev + 1; 
+            }
+            self.last_seen[u] = Some(self.position); 
+            self.position += 1; 
+            let run = self.position - self.start; 
+            let end = self.position; 
+            self.pending.extend(self.windows.iter().take_while(|w| **w <= run).map(|window| Marker { window: *window, end })); 
+        }
     }
 }
 
 impl AOCSolutions for Day6 {
     fn get_star_1(input: &str) -> Result<i64, ()> {
-        match Day6::pooled_decode(input.as_bytes(), 4, 8) {
-            Some(v) => return Ok(v.try_into().unwrap()), 
-            None => return Err(()), 
+        match Day6::find_marker(input.as_bytes(), 4) {
+            Some(v) => Ok(v.try_into().unwrap()), 
+            None => Err(()), 
         }
     }
 
     fn get_star_2(input: &str) -> Result<i64, ()> {
-        match Day6::pooled_decode(input.as_bytes(), 14, 8) {
-            Some(v) => return Ok(v.try_into().unwrap()), 
-            None => return Err(()), 
+        match Day6::find_marker(input.as_bytes(), 14) {
+            Some(v) => Ok(v.try_into().unwrap()), 
+            None => Err(()), 
         }
     }
 }
 
 impl Day6 {
-    fn pooled_decode(input: &[u8], config: usize, thread_count: usize) -> Option<usize> {
+    /**
+    Returns the number of bytes read up to the end of the first `window` consecutive pairwise 
+    distinct bytes of `input`, or `None` if there is no such window. 
+
+    Sliding window in O(n): a table of the last index each byte value was seen at lets the window 
+    start jump past the previous occurrence of a repeated byte. 
+     */
+    pub fn find_marker(input: &[u8], window: usize) -> Option<usize> {
+        if window == 0 { return None; }
+        let mut last_seen: [Option<usize>; 256] = [None; 256]; 
+        let mut start: usize = 0; 
+        for (idx, u) in input.iter().enumerate() {
+            if let Some(prev) = last_seen[*u as usize].filter(|prev| *prev >= start) {
+                start = prev + 1; 
+            }
+            last_seen[*u as usize] = Some(idx); 
+            if idx + 1 - start == window {
+                return Some(idx + 1); 
+            }
+        }
+        None
+    }
+
+    /**
+    Returns the end of the first marker for each of `windows` (in the same order) in a single pass 
+    over `reader`, reading only as far as needed to find all of them. 
+     */
+    pub fn first_markers<R: Read>(reader: R, windows: &[usize]) -> io::Result<Vec<Option<usize>>> {
+        let mut firsts: Vec<Option<usize>> = vec![None; windows.len()]; 
+        for marker in MarkerStream::new(reader, windows) {
+            let marker = marker?; 
+            for (window, first) in windows.iter().zip(firsts.iter_mut()) {
+                if *window == marker.window && first.is_none() { *first = Some(marker.end); }
+            }
+            if firsts.iter().zip(windows).all(|(first, window)| first.is_some() || *window == 0) { break; }
+        }
+        Ok(firsts)
+    }
+
+    /**
+    Same as `find_marker`, checking every window on a `WorkerPool` of `thread_count` threads. 
+    Kept for comparison, see `benches/day6.rs`. 
+     */
+    pub fn pooled_decode(input: &[u8], config: usize, thread_count: usize) -> Option<usize> {
         let mut pooled_decoder = PooledDecoder::new(input.to_vec(), config, thread_count); 
         return pooled_decoder.execute(); 
     }
@@ -149,7 +231,32 @@ impl Day6 {
 mod tests {
     use crate::get_solutions::AOCSolutions;
 
-    use super::Day6; 
+    use super::{Day6, Marker, MarkerStream}; 
+    use std::io::{self, Read}; 
+
+    /// Reader handing out one byte per `read`, to exercise buffer refills.
+    struct TrickleReader<'a>(&'a [u8]); 
+
+    impl Read for TrickleReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.0.split_first() {
+                Some((u, rest)) if !buf.is_empty() => {
+                    buf[0] = *u; 
+                    self.0 = rest; 
+                    Ok(1)
+                }, 
+                _ => Ok(0), 
+            }
+        }
+    }
+
+    struct FailingReader; 
+
+    impl Read for FailingReader {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::other("[day6::tests::FailingReader] Expected failure"))
+        }
+    }
 
     const SAMPLE_1: &str = "wxzy"; // 4
     const SAMPLE_2: &str = "bvwbjplbgvbhsrlpgdmjqwftvncz"; // 5
@@ -172,6 +279,61 @@ mod tests {
         assert_eq!(Day6::pooled_decode(SAMPLE_4.as_bytes(), 4, 1).unwrap(), 11); 
     }
 
+    #[test]
+    fn test_pooled_decode_across_many_batches() {
+        // Far more batches than are kept in flight, with the marker in the middle of them
+        let mut data: Vec<u8> = (0..20_000).map(|idx| b'a' + (idx % 3) as u8).collect(); 
+        data[12_000..12_014].copy_from_slice(b"nopqrstuvwxyzA"); 
+        for threads in [1, 3] {
+            assert_eq!(Day6::pooled_decode(&data, 14, threads), Some(12_011)); 
+            assert_eq!(Day6::pooled_decode(&data, 14, threads), Day6::find_marker(&data, 14)); 
+            assert_eq!(Day6::pooled_decode(&data[..10_000], 4, threads), None); 
+        }
+    }
+
+    #[test]
+    fn test_find_marker() {
+        for (sample, expected) in [(SAMPLE_1, 4), (SAMPLE_2, 5), (SAMPLE_3, 10), (SAMPLE_4, 11)] {
+            assert_eq!(Day6::find_marker(sample.as_bytes(), 4), Some(expected)); 
+            assert_eq!(Day6::find_marker(sample.as_bytes(), 4), Day6::pooled_decode(sample.as_bytes(), 4, 4)); 
+        }
+        assert_eq!(Day6::find_marker(b"aaaa", 2), None); 
+        assert_eq!(Day6::find_marker(b"abca", 4), None); 
+        assert_eq!(Day6::find_marker(b"a", 1), Some(1)); 
+        assert_eq!(Day6::find_marker(b"abc", 0), None); 
+    }
+
+    #[test]
+    fn test_marker_stream_reports_every_marker() {
+        let data = SAMPLE_3.as_bytes(); 
+        let markers: Vec<Marker> = MarkerStream::new(TrickleReader(data), &[14, 4, 4, 0])
+            .collect::<io::Result<_>>()
+            .unwrap(); 
+        for window in [4, 14] {
+            let expected: Vec<usize> = (window..=data.len())
+                .filter(|end| Day6::is_packet_start_marker(&data[end - window..*end]))
+                .collect(); 
+            let actual: Vec<usize> = markers.iter().filter(|m| m.window == window).map(|m| m.end).collect(); 
+            assert_eq!(actual, expected); 
+        }
+        assert!(markers.windows(2).all(|pair| (pair[0].end, pair[0].window) < (pair[1].end, pair[1].window))); 
+    }
+
+    #[test]
+    fn test_first_markers() {
+        assert_eq!(Day6::first_markers(SAMPLE_2.as_bytes(), &[4, 14]).unwrap(), vec![Some(5), Some(23)]); 
+        assert_eq!(Day6::first_markers(TrickleReader(SAMPLE_4.as_bytes()), &[14, 4, 27]).unwrap(), vec![Some(26), Some(11), None]); 
+        assert!(Day6::first_markers(FailingReader, &[4]).is_err()); 
+        let mut stream = MarkerStream::new(FailingReader, &[4]); 
+        assert!(stream.next().unwrap().is_err()); 
+        assert!(stream.next().is_none()); 
+    }
+
+    #[test]
+    fn test_get_star_1() {
+        assert_eq!(Day6::get_star_1(SAMPLE_3).unwrap(), 10); 
+    }
+
     #[test]
     fn test_get_star_2() {
         assert_eq!(Day6::get_star_2(SAMPLE_2).unwrap(), 23); 
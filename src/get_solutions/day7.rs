@@ -1,141 +1,397 @@
-use std::collections::HashMap;
-use std::io::BufRead;  
+use std::collections::BTreeMap;
+use std::fmt; 
+use std::fs::{self, OpenOptions}; 
+use std::io::{self, BufRead};  
+use std::path::{Component, Path}; 
 use super::AOCSolutions; 
 
 pub struct Day7; 
 
-type FileSystem = HashMap<String, (usize, bool)>; // K: full path; V: (sum size, is_evaluated?)
+/**
+Node of a `FileTree`: a file of the given size, or a directory of named children (kept sorted by 
+name). 
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Node {
+    File(usize), 
+    Dir(BTreeMap<String, Node>), 
+}
 
-impl AOCSolutions for Day7 {
-    fn get_star_1(input: &str) -> Result<i64, ()> {
-        let fs = Day7::parse(&mut input.as_bytes());
-        let sum = fs.iter()
-            .filter_map(|(_, (size, _))| if size.to_owned() <= 100000 { Some(size.to_owned()) } else { None })
-            .reduce(|acc, rhs| acc + rhs );
-        if let None = sum {
-            return Ok(0); 
-        } else {
-            return Ok(sum.unwrap().try_into().unwrap()); 
+impl Node {
+    pub fn is_dir(&self) -> bool {
+        matches!(self, Node::Dir(_))
+    }
+
+    /// Size of a file, or cumulative size of everything below a directory.
+    pub fn size(&self) -> usize {
+        match self {
+            Node::File(size) => *size, 
+            Node::Dir(children) => children.values().map(Node::size).sum(), 
         }
     }
+}
 
-    fn get_star_2(input: &str) -> Result<i64, ()> {
-        const DISK_SIZE: usize = 70000000;
-        const UPDATE_SIZE: usize = 30000000; 
+/**
+Node found by `FileTree::walk`, with its full path and (cumulative) size. 
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry<'a> {
+    pub path: String, 
+    pub node: &'a Node, 
+    pub size: usize, 
+}
 
-        let fs = Day7::parse(&mut input.as_bytes()); 
-        let total_usage = fs.get("/").unwrap().0; 
-        if DISK_SIZE - total_usage >= UPDATE_SIZE { return Err(()); }
-        let threshold = UPDATE_SIZE - (DISK_SIZE - total_usage); 
-        let target_size = fs.iter()
-            .filter_map(|(_, (size, _))| if *size >= threshold { Some(*size) } else { None } )
-            .min();
-        if let Some(size) = target_size {
-            return Ok(size.try_into().unwrap()); 
-        } else {
-            return Err(()); 
+#[derive(Debug)]
+pub enum ParseErrorKind {
+    Io(io::Error), 
+    /// Command other than `cd <path>` and `ls`.
+    UnknownCommand(String), 
+    /// `ls` output that is neither `dir <name>` nor `<size> <name>`, or whose name is `.`, `..` or 
+    /// contains `/`.
+    InvalidListing(String), 
+    /// Path (or one of its ancestors) used as a directory is a file.
+    NotADirectory(String), 
+    /// Path listed both as a file and as a directory.
+    TypeConflict(String), 
+}
+
+#[derive(Debug)]
+pub struct ParseError {
+    /// 1-based line number.
+    pub line: usize, 
+    pub kind: ParseErrorKind, 
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?; 
+        match &self.kind {
+            ParseErrorKind::Io(e) => write!(f, "cannot read input: {}", e), 
+            ParseErrorKind::UnknownCommand(command) => write!(f, "unknown command \"{}\"", command), 
+            ParseErrorKind::InvalidListing(listing) => write!(f, "invalid `ls` output \"{}\"", listing), 
+            ParseErrorKind::NotADirectory(path) => write!(f, "`{}` is not a directory", path), 
+            ParseErrorKind::TypeConflict(path) => write!(f, "`{}` listed both as a file and as a directory", path), 
         }
     }
 }
 
-impl Day7 {
-    fn parse<R>(input: &mut R) -> FileSystem 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WarningKind {
+    /// `cd` into the given directory before any `ls` listed it.
+    UnlistedDirectory(String), 
+    /// `cd ..` at the root, which stays at the root.
+    AboveRoot, 
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    /// 1-based line number.
+    pub line: usize, 
+    pub kind: WarningKind, 
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?; 
+        match &self.kind {
+            WarningKind::UnlistedDirectory(path) => write!(f, "`cd` into never listed directory `{}`", path), 
+            WarningKind::AboveRoot => write!(f, "`cd ..` at the root"), 
+        }
+    }
+}
+
+/**
+Filesystem reconstructed from a shell transcript, rooted at `/`. 
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileTree {
+    root: Node, 
+}
+
+impl Default for FileTree {
+    fn default() -> Self {
+        FileTree { root: Node::Dir(BTreeMap::new()) }
+    }
+}
+
+impl FileTree {
+    /**
+    Replays the shell transcript in `input`, ignoring warnings (see `parse_with_warnings`). 
+     */
+    pub fn parse<R>(input: &mut R) -> Result<FileTree, ParseError> 
         where R: BufRead {
-        let mut fs = FileSystem::new(); 
-        let mut cwd = "".to_string(); 
-        fs.insert(cwd.clone(), (0, false)); 
-
-        loop {
-            let mut line = String::new(); 
-            if let Ok(read_amnt) = input.read_line(&mut line) {
-                if read_amnt == 0 { 
-                    fs.entry(cwd.clone()).and_modify(|(_, evaluated)| *evaluated = true ); 
-                    return fs; 
-                }
-            } else {
-                panic!("[Day7::parse] Cannot read from given `input`"); 
-            }
+        FileTree::parse_with_warnings(input).map(|(tree, _)| tree)
+    }
 
-            match line.find("$") {
-                Some(0) => { // Command
-                    fs.entry(cwd.clone()).and_modify(|(_, evaluated)| *evaluated = true ); 
+    /**
+    Replays the shell transcript in `input`. 
 
-                    let args: Vec<&str> = line.trim().split(' ').collect(); 
-                    if args.len() < 2 { panic!("[Day7::parse] Illegal command: {}", line.trim()); }
+    `cd` accepts absolute (`/a/b`) and relative multi-level paths (`a/b/..`), where `.` and empty 
+    components are ignored. Entries listed more than once (e.g., by repeated `ls` of the same 
+    directory) are deduplicated by name, a later listing replacing a file's size. 
 
-                    match (args.get(1), args.get(2)) {
-                        (Some(&"cd"), Some(&"..")) => { // cd w/ prev.lvl operator
-                            if cwd.as_str() != "/" { 
-                                cwd = cwd.rsplit_once('/').unwrap().0.to_string(); 
-                            } 
-                        }, 
-                        (Some(&"cd"), Some(&"/")) => { // cd to root
-                            cwd = "/".to_string(); 
-                        }, 
-                        (Some(&"cd"), Some(&dir_name)) => { // cd w/ dir_name
-                            if &cwd != "/" { cwd.push('/'); } 
-                            cwd.push_str(dir_name); 
-                        }, 
-                        (Some(&"ls"), None) => { // ls
-                            fs.entry(cwd.clone()).or_insert((0, false)); 
-                        }, 
-                        _ => { // undefined cmd
-                            panic!("[Day7::parse] Illegal command: {}", line.trim()); 
+    `cd` into a directory that was never listed creates it and `cd ..` at the root stays there; 
+    both are reported as warnings, in input order. Unknown commands, malformed `ls` output and 
+    files used as directories (or vice versa) are errors. 
+     */
+    pub fn parse_with_warnings<R>(input: &mut R) -> Result<(FileTree, Vec<Warning>), ParseError> 
+        where R: BufRead {
+        let mut tree = FileTree::default(); 
+        let mut warnings = Vec::new(); 
+        let mut cwd: Vec<String> = Vec::new(); 
+
+        for (idx, line) in input.lines().enumerate() {
+            let line_no = idx + 1; 
+            let error = |kind| ParseError { line: line_no, kind }; 
+            let line = line.map_err(|e| error(ParseErrorKind::Io(e)))?; 
+            let args: Vec<&str> = line.split_whitespace().collect(); 
+            match args[..] {
+                [] => continue, 
+                ["$", "cd", path] => {
+                    if path.starts_with('/') { cwd.clear(); }
+                    for name in path.split('/') {
+                        match name {
+                            "" | "." => (), 
+                            ".." => if cwd.pop().is_none() {
+                                warnings.push(Warning { line: line_no, kind: WarningKind::AboveRoot }); 
+                            }, 
+                            name => cwd.push(name.to_string()), 
                         }
                     }
-                }, 
-                None => { // info dump
-                    match fs.get(&cwd) {
-                        Some((_, false)) => { 
-                            if let Some(size) = line.trim().split(' ').next() { // first word in line: `dir` or size num
-                                if let Ok(size) = size.parse::<usize>() { // file found -- size num
-                                    Day7::update_all_sizes(size, &cwd, &mut fs); 
-                                } else if size == "dir" { // sub-directory found -- `dir`
-                                    ()
-                                } else {
-                                    panic!("[Day7::parse] Invalid `ls` output: {}", line.trim()); 
-                                }
-                            } else {
-                                panic!("[Day7::parse] Invalid `ls` output: {}", line.trim()); 
-                            }
+                    match tree.lookup_components(&cwd) {
+                        Some(Node::Dir(_)) => (), 
+                        Some(Node::File(_)) => return Err(error(ParseErrorKind::NotADirectory(FileTree::path_of(&cwd)))), 
+                        None => {
+                            warnings.push(Warning { line: line_no, kind: WarningKind::UnlistedDirectory(FileTree::path_of(&cwd)) }); 
+                            tree.dir_mut(&cwd).map_err(|path| error(ParseErrorKind::NotADirectory(path)))?; 
                         }, 
-                        Some((_, true)) => continue, 
-                        None => panic!("[Day7::parse] `{}` not found in filesystem", cwd.as_str()),  
+                    }
+                }, 
+                ["$", "ls"] => (), 
+                ["$", ..] => return Err(error(ParseErrorKind::UnknownCommand(line.trim().to_string()))), 
+                [_, name] if name.contains('/') || name == "." || name == ".." => {
+                    return Err(error(ParseErrorKind::InvalidListing(line.trim().to_string()))); 
+                }, 
+                [size_or_dir, name] => {
+                    let children = tree.dir_mut(&cwd).map_err(|path| error(ParseErrorKind::NotADirectory(path)))?; 
+                    let node = if size_or_dir == "dir" {
+                        Node::Dir(BTreeMap::new())
+                    } else {
+                        let size = size_or_dir.parse::<usize>()
+                            .map_err(|_| error(ParseErrorKind::InvalidListing(line.trim().to_string())))?; 
+                        Node::File(size)
                     }; 
+                    match children.get_mut(name) {
+                        Some(existing) if existing.is_dir() != node.is_dir() => {
+                            let mut path = cwd.clone(); 
+                            path.push(name.to_string()); 
+                            return Err(error(ParseErrorKind::TypeConflict(FileTree::path_of(&path)))); 
+                        }, 
+                        Some(Node::Dir(_)) => (), // Keep what is known of its contents
+                        Some(existing) => *existing = node, 
+                        None => { children.insert(name.to_string(), node); }, 
+                    }
                 }, 
-                Some(_) => { // Err
-                    panic!("[Day7::parse] Illegal command: {}", line.trim()); 
-                }
+                _ => return Err(error(ParseErrorKind::InvalidListing(line.trim().to_string()))), 
             }
         }
+        Ok((tree, warnings))
+    }
+
+    pub fn root(&self) -> &Node {
+        &self.root
     }
 
-    fn update_all_sizes(delta: usize, cwd: &str, fs: &mut FileSystem) {
-        println!("[Day7::update_all_sizes] Found file in `{}` of size `{}`", cwd, delta); 
-        let mut cwd = String::from(cwd);
-        if !cwd.ends_with('/') { cwd.push('/') }; 
-        let mut pwd = String::new(); 
-        
-        while !cwd.is_empty() {
-            let slash_idx = cwd.find('/').unwrap(); 
-            let uri: String = cwd.drain(0..=slash_idx).collect();
-            match pwd.as_str() {
-                "" => pwd.push_str(&uri),   
-                "/" => pwd.push_str(&uri[..uri.len() - 1]), 
-                _ => {
-                    pwd.push('/');
-                    pwd.push_str(&uri[..uri.len() - 1]); 
+    /**
+    Looks up a full path such as `/a/e` (`/` being the root). 
+     */
+    pub fn lookup(&self, path: &str) -> Option<&Node> {
+        let components: Vec<String> = path.split('/')
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect(); 
+        self.lookup_components(&components)
+    }
+
+    /// Cumulative size of the node at `path`, like `du -s`.
+    pub fn du(&self, path: &str) -> Option<usize> {
+        self.lookup(path).map(Node::size)
+    }
+
+    /**
+    Every node in pre-order (children sorted by name), starting with the root, with cumulative 
+    sizes computed in one pass. 
+     */
+    pub fn walk(&self) -> Vec<Entry<'_>> {
+        let mut entries = Vec::new(); 
+        FileTree::walk_into(&self.root, "/".to_string(), &mut entries); 
+        entries
+    }
+
+    /// Nodes matching `predicate`, in `walk` order.
+    pub fn find<P>(&self, predicate: P) -> Vec<Entry<'_>> 
+        where P: Fn(&Entry) -> bool {
+        self.walk().into_iter().filter(|e| predicate(e)).collect()
+    }
+
+    /**
+    Renders the tree like `tree`, children sorted by name, each node followed by its (cumulative) 
+    size. Directories are marked with a trailing `/`: 
+
+    ```text
+    / (48381165)
+    ├── a/ (94853)
+    │   ├── e/ (584)
+    │   │   └── i (584)
+    ...
+    ```
+     */
+    pub fn render_tree(&self) -> String {
+        let mut out = format!("/ ({})\n", self.root.size()); 
+        FileTree::render_children(&self.root, "", &mut out); 
+        out
+    }
+
+    /**
+    Materialises the tree under `dest` (created if missing): one directory per directory and one 
+    sparse file of the recorded size per file, to be cross-checked with `du --apparent-size -b` 
+    (which also counts the directories themselves) without using the disk space. 
+
+    Existing files are never overwritten. Any name other than a single plain path component (e.g., 
+    `..`, `a/b` or `/abs`) is rejected with `io::ErrorKind::InvalidInput` before anything is 
+    created for it, so nothing is ever written outside `dest`. 
+     */
+    pub fn export<P: AsRef<Path>>(&self, dest: P) -> io::Result<()> {
+        fs::create_dir_all(dest.as_ref())?; 
+        FileTree::export_children(&self.root, dest.as_ref())
+    }
+
+    fn render_children(node: &Node, prefix: &str, out: &mut String) {
+        let Node::Dir(children) = node else { return; }; 
+        for (idx, (name, child)) in children.iter().enumerate() {
+            let is_last = idx + 1 == children.len(); 
+            let (branch, indent) = if is_last { ("└── ", "    ") } else { ("├── ", "│   ") }; 
+            let marker = if child.is_dir() { "/" } else { "" }; 
+            out.push_str(&format!("{}{}{}{} ({})\n", prefix, branch, name, marker, child.size())); 
+            FileTree::render_children(child, &format!("{}{}", prefix, indent), out); 
+        }
+    }
+
+    fn export_children(node: &Node, dir: &Path) -> io::Result<()> {
+        let Node::Dir(children) = node else { return Ok(()); }; 
+        for (name, child) in children {
+            if !FileTree::is_plain_name(name) {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("[Day7::FileTree::export] Refusing to export `{}`", name))); 
+            }
+            let path = dir.join(name); 
+            match child {
+                Node::Dir(_) => {
+                    fs::create_dir_all(&path)?; 
+                    FileTree::export_children(child, &path)?; 
+                }, 
+                Node::File(size) => {
+                    // `set_len` extends without writing, leaving a hole on filesystems that support it
+                    let file = OpenOptions::new().write(true).create_new(true).open(&path)?; 
+                    file.set_len(*size as u64)?; 
                 }, 
             }
-            println!("[Day7::update_all_sizes] Modifying `{}`...", pwd.as_str());  
-            fs.entry(pwd.clone()).and_modify(|(size, _)| *size += delta ); 
         }
+        Ok(())
+    }
+
+    /// Whether `name` joined to a directory stays directly inside it.
+    fn is_plain_name(name: &str) -> bool {
+        let mut components = Path::new(name).components(); 
+        !name.contains(['/', '\\']) 
+            && matches!(components.next(), Some(Component::Normal(_))) 
+            && components.next().is_none()
+    }
+
+    fn walk_into<'a>(node: &'a Node, path: String, entries: &mut Vec<Entry<'a>>) -> usize {
+        let idx = entries.len(); 
+        entries.push(Entry { path: path.clone(), node, size: 0 }); 
+        let size = match node {
+            Node::File(size) => *size, 
+            Node::Dir(children) => children.iter()
+                .map(|(name, child)| {
+                    let child_path = if path == "/" { format!("/{}", name) } else { format!("{}/{}", path, name) }; 
+                    FileTree::walk_into(child, child_path, entries)
+                })
+                .sum(), 
+        }; 
+        entries[idx].size = size; 
+        size
+    }
+
+    /**
+    Children of the directory at `path` (as names from the root), created if missing. Returns the 
+    full path of the first component that is a file, if any. 
+     */
+    fn dir_mut(&mut self, path: &[String]) -> Result<&mut BTreeMap<String, Node>, String> {
+        let mut node = &mut self.root; 
+        for (depth, name) in path.iter().enumerate() {
+            let Node::Dir(children) = node else {
+                return Err(FileTree::path_of(&path[..depth])); 
+            }; 
+            node = children.entry(name.clone()).or_insert_with(|| Node::Dir(BTreeMap::new())); 
+        }
+        match node {
+            Node::Dir(children) => Ok(children), 
+            Node::File(_) => Err(FileTree::path_of(path)), 
+        }
+    }
+
+    fn lookup_components(&self, path: &[String]) -> Option<&Node> {
+        path.iter().try_fold(&self.root, |node, name| match node {
+            Node::Dir(children) => children.get(name), 
+            Node::File(_) => None, 
+        })
+    }
+
+    fn path_of(components: &[String]) -> String {
+        format!("/{}", components.join("/"))
+    }
+}
+
+impl AOCSolutions for Day7 {
+    fn get_star_1(input: &str) -> Result<i64, ()> {
+        let tree = Day7::tree_or_report(input, "get_star_1")?; 
+        let sum: usize = tree.find(|e| e.node.is_dir() && e.size <= 100000)
+            .iter()
+            .map(|e| e.size)
+            .sum(); 
+        sum.try_into().map_err(|_| ())
+    }
+
+    fn get_star_2(input: &str) -> Result<i64, ()> {
+        const DISK_SIZE: usize = 70000000;
+        const UPDATE_SIZE: usize = 30000000; 
+
+        let tree = Day7::tree_or_report(input, "get_star_2")?; 
+        let total_usage = tree.du("/").unwrap(); 
+        if DISK_SIZE - total_usage >= UPDATE_SIZE { return Err(()); }
+        let threshold = UPDATE_SIZE - (DISK_SIZE - total_usage); 
+        let target_size = tree.find(|e| e.node.is_dir() && e.size >= threshold)
+            .iter()
+            .map(|e| e.size)
+            .min();
+        target_size.ok_or(())?.try_into().map_err(|_| ())
+    }
+}
+
+impl Day7 {
+    fn tree_or_report(input: &str, caller: &str) -> Result<FileTree, ()> {
+        let (tree, warnings) = FileTree::parse_with_warnings(&mut input.as_bytes())
+            .map_err(|e| eprintln!("[Day7::{}] Malformed transcript: {}", caller, e))?; 
+        warnings.iter().for_each(|w| eprintln!("[Day7::{}] Warning: {}", caller, w)); 
+        Ok(tree)
     }
 }
 
 #[cfg(test)]
 mod tests{
-    use super::Day7; 
+    use super::{Day7, FileTree, Node, ParseErrorKind, Warning, WarningKind}; 
+    use std::collections::BTreeMap; 
     use super::AOCSolutions; 
 
     const SAMPLE_INPUT: &str = r"$ cd /
@@ -171,4 +427,122 @@ $ ls
     fn test_get_star_2() {
         assert_eq!(Day7::get_star_2(SAMPLE_INPUT).unwrap(), 24933642); 
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_file_tree_queries() {
+        let tree = FileTree::parse(&mut SAMPLE_INPUT.as_bytes()).unwrap(); 
+        assert_eq!(tree.du("/"), Some(48381165)); 
+        assert_eq!(tree.du("/a"), Some(94853)); 
+        assert_eq!(tree.du("/a/e/"), Some(584)); 
+        assert_eq!(tree.lookup("/d/d.log"), Some(&Node::File(8033020))); 
+        assert_eq!(tree.lookup("/b.txt/x"), None); 
+        assert_eq!(tree.lookup("/nope"), None); 
+
+        let paths: Vec<String> = tree.walk().into_iter().map(|e| e.path).collect(); 
+        assert_eq!(paths[..4], ["/", "/a", "/a/e", "/a/e/i"]); 
+        assert_eq!(paths.len(), 14); 
+        let small_files: Vec<String> = tree.find(|e| !e.node.is_dir() && e.size < 10000).into_iter().map(|e| e.path).collect(); 
+        assert_eq!(small_files, ["/a/e/i", "/a/g"]); 
+    }
+
+    #[test]
+    fn test_repeated_listing_is_deduplicated() {
+        let input = format!("{}\n$ ls\n4060174 j\n8033020 d.log\n$ cd /\n$ ls\ndir a\n14848514 b.txt", SAMPLE_INPUT); 
+        let tree = FileTree::parse(&mut input.as_bytes()).unwrap(); 
+        assert_eq!(tree, FileTree::parse(&mut SAMPLE_INPUT.as_bytes()).unwrap()); 
+        assert_eq!(Day7::get_star_1(&input).unwrap(), 95437); 
+    }
+
+    #[test]
+    fn test_multi_level_cd() {
+        let input = "$ cd /\n$ ls\ndir a\n$ cd a\n$ ls\ndir b\n$ cd /a/b\n$ ls\n10 x\n$ cd ../b/./..\n$ ls\n20 y\n$ cd b/\n$ cd /\n$ cd a//b\n$ ls\n30 z"; 
+        let (tree, warnings) = FileTree::parse_with_warnings(&mut input.as_bytes()).unwrap(); 
+        assert!(warnings.is_empty()); 
+        assert_eq!(tree.du("/a/b"), Some(40)); 
+        assert_eq!(tree.du("/a/y"), Some(20)); 
+        assert_eq!(tree.du("/"), Some(60)); 
+    }
+
+    #[test]
+    fn test_transcript_warnings() {
+        let input = "$ cd ..\n$ cd /\n$ ls\ndir a\n$ cd a/c\n$ ls\n5 f\n$ cd /x"; 
+        let (tree, warnings) = FileTree::parse_with_warnings(&mut input.as_bytes()).unwrap(); 
+        assert_eq!(warnings, [
+            Warning { line: 1, kind: WarningKind::AboveRoot }, 
+            Warning { line: 5, kind: WarningKind::UnlistedDirectory("/a/c".to_string()) }, 
+            Warning { line: 8, kind: WarningKind::UnlistedDirectory("/x".to_string()) }, 
+        ]); 
+        assert_eq!(tree.du("/a"), Some(5)); 
+        assert!(tree.lookup("/x").is_some_and(Node::is_dir)); 
+    }
+
+    #[test]
+    fn test_transcript_errors() {
+        let error_at = |input: &str| FileTree::parse(&mut input.as_bytes()).unwrap_err(); 
+
+        let error = error_at("$ cd /\n$ ls\n5 f\n$ pwd"); 
+        assert_eq!(error.line, 4); 
+        assert!(matches!(error.kind, ParseErrorKind::UnknownCommand(ref c) if c == "$ pwd")); 
+        assert_eq!(error.to_string(), "line 4: unknown command \"$ pwd\""); 
+
+        assert!(matches!(error_at("$ ls\nfive f").kind, ParseErrorKind::InvalidListing(_))); 
+        for listing in ["5 a/b", "dir ..", "5 .", "dir /etc"] {
+            let error = error_at(&format!("$ ls\n{}", listing)); 
+            assert_eq!(error.line, 2); 
+            assert!(matches!(error.kind, ParseErrorKind::InvalidListing(ref l) if l == listing)); 
+        }
+        assert!(matches!(error_at("$ ls\n5 f\n$ cd f/g").kind, ParseErrorKind::NotADirectory(ref p) if p == "/f")); 
+        assert!(matches!(error_at("$ ls\ndir f\n$ ls\n5 f").kind, ParseErrorKind::TypeConflict(ref p) if p == "/f")); 
+        assert!(Day7::get_star_1("$ cd /\n$ rm -rf a").is_err()); 
+    }
+
+    #[test]
+    fn test_render_tree() {
+        let tree = FileTree::parse(&mut SAMPLE_INPUT.as_bytes()).unwrap(); 
+        assert_eq!(tree.render_tree(), "\
+/ (48381165)
+├── a/ (94853)
+│   ├── e/ (584)
+│   │   └── i (584)
+│   ├── f (29116)
+│   ├── g (2557)
+│   └── h.lst (62596)
+├── b.txt (14848514)
+├── c.dat (8504156)
+└── d/ (24933642)
+    ├── d.ext (5626152)
+    ├── d.log (8033020)
+    ├── j (4060174)
+    └── k (7214296)
+"); 
+    }
+
+    #[test]
+    fn test_export() {
+        let base = std::env::temp_dir().join(format!("day7_export_{}", std::process::id())); 
+        let dest = base.join("dest"); 
+        let tree = FileTree::parse(&mut SAMPLE_INPUT.as_bytes()).unwrap(); 
+        tree.export(&dest).unwrap(); 
+        assert_eq!(std::fs::metadata(dest.join("a/e/i")).unwrap().len(), 584); 
+        assert_eq!(std::fs::metadata(dest.join("d/k")).unwrap().len(), 7214296); 
+        assert!(dest.join("a/e").is_dir()); 
+        // Never overwrites
+        assert!(tree.export(&dest).is_err()); 
+        std::fs::remove_dir_all(&dest).unwrap(); 
+
+        // Not accepted by the parser, but `Node` can be built directly
+        let absolute = base.join("abs"); 
+        let names = ["..", "../x", "a/../../x", "a/b", "a\\b", ".", "", absolute.to_str().unwrap()]; 
+        for name in names {
+            for node in [Node::File(5), Node::Dir(BTreeMap::new())] {
+                let escaping = FileTree { root: Node::Dir(BTreeMap::from([(name.to_string(), node)])) }; 
+                assert_eq!(escaping.export(&dest).unwrap_err().kind(), std::io::ErrorKind::InvalidInput, "{:?}", name); 
+            }
+        }
+        // Nothing created next to `dest` (`../x`, `/abs`) or inside it
+        let created: Vec<_> = std::fs::read_dir(&base).unwrap().map(|e| e.unwrap().file_name()).collect(); 
+        assert_eq!(created, ["dest"]); 
+        assert_eq!(std::fs::read_dir(&dest).unwrap().count(), 0); 
+        std::fs::remove_dir_all(&base).unwrap(); 
+    }
+}
@@ -23,4 +23,62 @@ pub trait AOCSolutions {
     fn get_star_2(input: &str) -> Result<i64, ()>; 
 }
 
+pub type StarFn = fn(&str) -> Result<i64, ()>;
+
+/**
+Registry entry of a solved day, holding both stars of its `AOCSolutions` implementation as plain
+function pointers so that days can be iterated over without knowing their concrete types.
+ */
+#[derive(Clone, Copy)]
+pub struct Solution {
+    pub day: usize,
+    pub star_1: StarFn,
+    pub star_2: StarFn,
+}
+
+impl Solution {
+    pub fn star(&self, star: usize) -> Option<StarFn> {
+        match star {
+            1 => Some(self.star_1),
+            2 => Some(self.star_2),
+            _ => None,
+        }
+    }
+}
+
+macro_rules! solution {
+    ($day:literal, $sol:ty) => {
+        Solution {
+            day: $day,
+            star_1: <$sol as AOCSolutions>::get_star_1,
+            star_2: <$sol as AOCSolutions>::get_star_2,
+        }
+    };
+}
+
+/**
+All registered solutions, in order of day. `new-day` appends to this list.
+ */
+pub const SOLUTIONS: &[Solution] = &[
+    solution!(1, day1::Day1),
+    solution!(2, day2::Day2),
+    solution!(3, day3::Day3),
+    solution!(4, day4::Day4),
+    solution!(5, day5::Day5),
+    solution!(6, day6::Day6),
+    solution!(7, day7::Day7),
+    solution!(8, day8::Day8),
+    solution!(9, day9::Day9),
+    solution!(10, day10::Day10),
+    solution!(11, day11::Day11),
+    solution!(12, day12::Day12),
+    solution!(13, day13::Day13),
+    solution!(14, day14::Day14),
+    solution!(15, day15::Day15),
+];
+
+pub fn find_solution(day: usize) -> Option<&'static Solution> {
+    SOLUTIONS.iter().find(|s| s.day == day)
+}
+
 
@@ -1,7 +1,14 @@
 use std::{path::Path, fs::File};
 use std::io::{Error, Read}; 
 
+pub mod alloc_stats;
+pub mod answers;
 pub mod get_solutions; 
+pub mod plugins;
+pub mod pool;
+pub mod runner;
+pub mod scaffold;
+pub mod server;
 
 pub fn parse_to_string<P: AsRef<Path>>(path: P) -> Result<String, Error> {
     let rf = File::open(path); 
@@ -1,18 +1,327 @@
-use std::env; 
-
-use advent_of_rust_22::get_solutions::AOCSolutions;
-use advent_of_rust_22::get_solutions::*; 
-use day15::Day15; 
-
-// TODO: Write a proper CLI. 
-fn main() {
-    let arguments: Vec<String> = env::args().collect(); 
-    assert!(arguments.len() == 2); 
-    let path = &arguments[1];
-    //let path = "./inputs/day14/input"; 
-    
-    let input = advent_of_rust_22::parse_to_string(path).unwrap(); 
-
-    println!("{}", Day15::get_star_1(input.as_str()).unwrap());
-    println!("{}", Day15::get_star_2(input.as_str()).unwrap()); 
+use std::env;
+use std::net::TcpListener;
+use std::process::ExitCode;
+
+use advent_of_rust_22::answers::Answers;
+use advent_of_rust_22::get_solutions::day5::{self, Day5};
+use advent_of_rust_22::get_solutions::day7::FileTree;
+use advent_of_rust_22::get_solutions::{Solution, SOLUTIONS};
+use advent_of_rust_22::plugins::{self, PluginSolution};
+use advent_of_rust_22::runner::{self, Budget, RunError, RunReport};
+use advent_of_rust_22::scaffold;
+use advent_of_rust_22::server::{self, ServerConfig};
+
+// Opt-in, since counting every allocation slows down allocation-heavy days
+#[cfg(feature = "alloc-stats")]
+#[global_allocator]
+static ALLOCATOR: advent_of_rust_22::alloc_stats::CountingAllocator = advent_of_rust_22::alloc_stats::CountingAllocator;
+
+const USAGE: &str = "Usage:
+    advent_of_rust_22 run <day> [input_path] [options]
+    advent_of_rust_22 run --all [options]
+    advent_of_rust_22 verify [options]
+    advent_of_rust_22 crane <9000|9001|limited-<n>> [input_path]
+    advent_of_rust_22 tree [input_path]
+    advent_of_rust_22 export <dest_dir> [input_path]
+    advent_of_rust_22 new-day <day>
+    advent_of_rust_22 serve [address]
+
+Options:
+    --timeout <secs>|<day>.<star>=<secs>    Wall-clock budget, for all stars or a single one
+    --plugins <dir>                         Directory of solution plugins [default: ./plugins]";
+
+const DEFAULT_SERVE_ADDR: &str = "127.0.0.1:8080";
+const DEFAULT_PLUGIN_DIR: &str = "./plugins";
+
+struct Options {
+    budget: Budget,
+    plugin_dir: String,
+}
+
+/**
+A built-in or plugin solution, as seen by the runner.
+ */
+enum Source<'a> {
+    Builtin(&'static Solution),
+    Plugin(&'a PluginSolution),
+}
+
+impl Source<'_> {
+    fn day(&self) -> usize {
+        match self {
+            Source::Builtin(s) => s.day,
+            Source::Plugin(p) => p.day,
+        }
+    }
+
+    fn label(&self, star: usize) -> String {
+        match self {
+            Source::Builtin(s) => format!("Day {} star {}", s.day, star),
+            Source::Plugin(p) => format!("Day {} star {} [{}]", p.day, star, p.origin.display()),
+        }
+    }
+
+    fn run_star(&self, star: usize, input: String, budget: &Budget) -> Result<RunReport, RunError> {
+        let limit = budget.get(self.day(), star);
+        match self {
+            Source::Builtin(s) => runner::run_star(s.star(star).unwrap(), input, limit),
+            Source::Plugin(p) => {
+                let plugin_star = p.star(star).unwrap();
+                runner::run_star(move |input| plugin_star.call(input), input, limit)
+            },
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let arguments: Vec<String> = env::args().skip(1).collect();
+    let (positionals, options) = match split_options(&arguments) {
+        Ok(split) => split,
+        Err(msg) => {
+            eprintln!("{}", msg);
+            return ExitCode::FAILURE;
+        },
+    };
+
+    let result = match positionals[..] {
+        ["run", "--all"] => run_all(&options),
+        ["run", day] => run(day, None, &options),
+        ["run", day, path] => run(day, Some(path), &options),
+        ["verify"] => verify(&options),
+        ["crane", model] => crane(model, None),
+        ["crane", model, path] => crane(model, Some(path)),
+        ["tree"] => tree(None),
+        ["tree", path] => tree(Some(path)),
+        ["export", dest] => export(dest, None),
+        ["export", dest, path] => export(dest, Some(path)),
+        ["new-day", day] => new_day(day),
+        ["serve"] => serve(DEFAULT_SERVE_ADDR),
+        ["serve", addr] => serve(addr),
+        _ => Err(USAGE.to_string()),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(msg) => {
+            eprintln!("{}", msg);
+            ExitCode::FAILURE
+        },
+    }
+}
+
+/**
+Separates `--timeout <spec>` and `--plugins <dir>` options from positional arguments.
+ */
+fn split_options(arguments: &[String]) -> Result<(Vec<&str>, Options), String> {
+    let mut positionals = Vec::with_capacity(arguments.len());
+    let mut options = Options { budget: Budget::default(), plugin_dir: DEFAULT_PLUGIN_DIR.to_string() };
+    let mut arg_iter = arguments.iter();
+    while let Some(arg) = arg_iter.next() {
+        if arg == "--timeout" || arg == "--plugins" {
+            let value = arg_iter.next()
+                .ok_or_else(|| format!("[main::split_options] Missing value for `{}`\n{}", arg, USAGE))?;
+            if arg == "--timeout" {
+                options.budget.apply(value)?;
+            } else {
+                options.plugin_dir = value.clone();
+            }
+        } else {
+            positionals.push(arg.as_str());
+        }
+    }
+    Ok((positionals, options))
+}
+
+fn parse_day(day: &str) -> Result<usize, String> {
+    day.parse().map_err(|_| format!("[main::parse_day] Invalid day: \"{}\"", day))
+}
+
+fn default_input_path(day: usize) -> String {
+    format!("./inputs/day{}/input", day)
+}
+
+fn default_answers_path(day: usize) -> String {
+    format!("./inputs/day{}/answers", day)
+}
+
+fn load_plugins(options: &Options) -> Result<Vec<PluginSolution>, String> {
+    plugins::load_plugins(&options.plugin_dir)
+        .map_err(|e| format!("[main::load_plugins] Cannot read `{}`: {}", options.plugin_dir, e))
+}
+
+/**
+Built-in solutions followed by plugin solutions.
+ */
+fn all_sources(plugins: &[PluginSolution]) -> Vec<Source<'_>> {
+    SOLUTIONS.iter().map(Source::Builtin)
+        .chain(plugins.iter().map(Source::Plugin))
+        .collect()
+}
+
+fn run(day: &str, path: Option<&str>, options: &Options) -> Result<(), String> {
+    let day = parse_day(day)?;
+    let plugins = load_plugins(options)?;
+    let sources: Vec<Source> = all_sources(&plugins).into_iter().filter(|s| s.day() == day).collect();
+    if sources.is_empty() {
+        return Err(format!("[main::run] Day {} is not solved yet", day));
+    }
+
+    let path = path.map(String::from).unwrap_or_else(|| default_input_path(day));
+    let failures = sources.iter().filter(|s| !run_solution(s, &path, &options.budget)).count();
+    if failures == 0 { Ok(()) } else { Err(format!("[main::run] Day {} failed", day)) }
+}
+
+fn run_all(options: &Options) -> Result<(), String> {
+    let plugins = load_plugins(options)?;
+    let failures: Vec<usize> = all_sources(&plugins).iter()
+        .filter(|s| !run_solution(s, &default_input_path(s.day()), &options.budget))
+        .map(Source::day)
+        .collect();
+    if failures.is_empty() { Ok(()) } else { Err(format!("[main::run_all] Failed days: {:?}", failures)) }
+}
+
+/**
+Runs every star of every built-in and plugin solution that has a recorded answer (see `Answers`)
+and compares the two, printing one line per star. Stars without a recorded answer are listed but
+do not fail the verification, unless no star has a recorded answer at all.
+ */
+fn verify(options: &Options) -> Result<(), String> {
+    let plugins = load_plugins(options)?;
+    let mut failures: Vec<String> = Vec::new();
+    let mut checked = 0;
+    for source in all_sources(&plugins) {
+        let day = source.day();
+        let answers = match Answers::load(default_answers_path(day)) {
+            Ok(answers) => answers,
+            Err(e) => {
+                eprintln!("[main::verify] Cannot read answers of day {}: {}", day, e);
+                failures.push(format!("Day {}", day));
+                continue;
+            },
+        };
+        let input = match advent_of_rust_22::parse_to_string(default_input_path(day)) {
+            Ok(input) => input,
+            Err(e) => {
+                eprintln!("[main::verify] Cannot read input of day {}: {}", day, e);
+                failures.push(format!("Day {}", day));
+                continue;
+            },
+        };
+
+        for star in [1, 2] {
+            let label = source.label(star);
+            let Some(expected) = answers.get(star) else {
+                println!("{}: no recorded answer", label);
+                continue;
+            };
+            checked += 1;
+            match source.run_star(star, input.clone(), &options.budget) {
+                Ok(report) if report.answer == expected => println!("{}: ok", label),
+                Ok(report) => {
+                    eprintln!("{}: expected {}, got {}", label, expected, report.answer);
+                    failures.push(label);
+                },
+                Err(e) => {
+                    let outcome = match e {
+                        RunError::Failed => "failed".to_string(),
+                        RunError::TimedOut(limit) => format!("timed out after {:?}", limit),
+                        RunError::Panicked => "panicked".to_string(),
+                    };
+                    eprintln!("{}: expected {}, {}", label, expected, outcome);
+                    failures.push(label);
+                },
+            }
+        }
+    }
+    if checked == 0 {
+        return Err("[main::verify] No recorded answers, nothing was checked".to_string());
+    }
+    if failures.is_empty() { Ok(()) } else { Err(format!("[main::verify] Failed: {}", failures.join(", "))) }
+}
+
+/**
+Runs both stars of `source` against the input at `path`, printing one line per star. Returns
+whether both stars succeeded.
+ */
+fn run_solution(source: &Source, path: &str, budget: &Budget) -> bool {
+    let input = match advent_of_rust_22::parse_to_string(path) {
+        Ok(input) => input,
+        Err(e) => {
+            eprintln!("[main::run_solution] Cannot read `{}`: {}", path, e);
+            return false;
+        },
+    };
+
+    let mut all_ok = true;
+    for star in [1, 2] {
+        let label = source.label(star);
+        match source.run_star(star, input.clone(), budget) {
+            Ok(report) => match report.memory {
+                Some(memory) => println!("{}: {} ({:?}, {})", label, report.answer, report.elapsed, memory),
+                None => println!("{}: {} ({:?})", label, report.answer, report.elapsed),
+            },
+            Err(e) => {
+                all_ok = false;
+                match e {
+                    RunError::Failed => eprintln!("{}: failed on `{}`", label, path),
+                    RunError::TimedOut(limit) => eprintln!("{}: timed out after {:?}", label, limit),
+                    RunError::Panicked => eprintln!("{}: panicked", label),
+                }
+            },
+        }
+    }
+    all_ok
+}
+
+/**
+Runs day 5's rearrangement with the named crane model and prints the resulting top crates.
+ */
+fn crane(model: &str, path: Option<&str>) -> Result<(), String> {
+    let crane = day5::crate_mover_by_name(model)
+        .ok_or_else(|| format!("[main::crane] Unknown crane model: \"{}\"\n{}", model, USAGE))?;
+    let path = path.map(String::from).unwrap_or_else(|| default_input_path(5));
+    let input = advent_of_rust_22::parse_to_string(&path)
+        .map_err(|e| format!("[main::crane] Cannot read `{}`: {}", path, e))?;
+    let top_crates = Day5::top_crates(&input, crane.as_ref())
+        .ok_or_else(|| format!("[main::crane] CrateMover {} failed on `{}`", crane.name(), path))?;
+    println!("CrateMover {}: {}", crane.name(), top_crates);
+    Ok(())
+}
+
+fn tree(path: Option<&str>) -> Result<(), String> {
+    let tree = file_tree(path, "tree")?;
+    print!("{}", tree.render_tree());
+    Ok(())
+}
+
+fn export(dest: &str, path: Option<&str>) -> Result<(), String> {
+    let tree = file_tree(path, "export")?;
+    tree.export(dest).map_err(|e| format!("[main::export] Cannot export to `{}`: {}", dest, e))?;
+    println!("Exported {} bytes to `{}`", tree.root().size(), dest);
+    Ok(())
+}
+
+/// Day 7 filesystem reconstructed from `path` (or the default day 7 input), warnings on stderr.
+fn file_tree(path: Option<&str>, caller: &str) -> Result<FileTree, String> {
+    let path = path.map(String::from).unwrap_or_else(|| default_input_path(7));
+    let input = advent_of_rust_22::parse_to_string(&path)
+        .map_err(|e| format!("[main::{}] Cannot read `{}`: {}", caller, path, e))?;
+    let (tree, warnings) = FileTree::parse_with_warnings(&mut input.as_bytes())
+        .map_err(|e| format!("[main::{}] Malformed transcript `{}`: {}", caller, path, e))?;
+    warnings.iter().for_each(|w| eprintln!("[main::{}] Warning: {}", caller, w));
+    Ok(tree)
+}
+
+fn new_day(day: &str) -> Result<(), String> {
+    let day = parse_day(day)?;
+    scaffold::new_day(".", day).map_err(|e| e.to_string())?;
+    println!("Created `src/get_solutions/day{}.rs` and `inputs/day{}/input`", day, day);
+    Ok(())
+}
+
+fn serve(addr: &str) -> Result<(), String> {
+    let listener = TcpListener::bind(addr)
+        .map_err(|e| format!("[main::serve] Cannot bind `{}`: {}", addr, e))?;
+    eprintln!("[main::serve] Listening on http://{}", addr);
+    server::serve(listener, ServerConfig::default()).map_err(|e| e.to_string())
 }
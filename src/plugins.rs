@@ -0,0 +1,366 @@
+/*!
+Loading of externally compiled solutions from `cdylib` shared objects.
+
+A plugin crate depends on this crate, implements `AOCSolutions` as usual and exports its days with
+`export_plugin!`, which generates the C-ABI entry point `aoc_plugin_entries`:
+
+```ignore
+pub struct Day16;
+impl AOCSolutions for Day16 { /* ... */ }
+
+advent_of_rust_22::export_plugin!(16 => Day16);
+```
+
+The host passes its cancellation flag to every star call, so plugin solvers can poll
+`runner::is_cancelled` like built-in ones. Loaded libraries are never unloaded, so that solver
+threads abandoned after a timeout remain valid.
+ */
+
+use std::ffi::{c_int, CString};
+use std::io::{Error, ErrorKind};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::{fs, ptr, slice};
+
+use crate::get_solutions::AOCSolutions;
+use crate::runner;
+
+/// Bumped whenever the layout of `PluginEntry` or the meaning of its fields changes.
+pub const PLUGIN_ABI_VERSION: u32 = 2;
+
+/// Name of the symbol every plugin exports, see `PluginEntriesFn`.
+pub const PLUGIN_ENTRY_SYMBOL: &str = "aoc_plugin_entries";
+
+/**
+C-ABI star function: solves the UTF-8 `input` of `input_len` bytes, writes the result to `answer`
+and returns 0 on success, non-zero otherwise. `cancel` is set by the caller once the star should
+stop; it may be null if the call cannot be cancelled.
+ */
+pub type PluginStarFn = unsafe extern "C" fn(
+    input: *const u8,
+    input_len: usize,
+    cancel: *const AtomicBool,
+    answer: *mut i64,
+) -> c_int;
+
+/**
+C-ABI entry point: writes the number of entries to `count` and returns a pointer to a static array
+of that many `PluginEntry`s.
+ */
+pub type PluginEntriesFn = unsafe extern "C" fn(count: *mut usize) -> *const PluginEntry;
+
+/**
+Solution exported by a plugin. `abi_version` must stay the first field in every version, since it
+is read before the rest of the layout is trusted.
+ */
+#[repr(C)]
+pub struct PluginEntry {
+    pub abi_version: u32,
+    pub day: u32,
+    pub star_1: PluginStarFn,
+    pub star_2: PluginStarFn,
+}
+
+impl PluginEntry {
+    pub const fn new<S: AOCSolutions>(day: u32) -> PluginEntry {
+        PluginEntry {
+            abi_version: PLUGIN_ABI_VERSION,
+            day,
+            star_1: star_trampoline::<S, 1>,
+            star_2: star_trampoline::<S, 2>,
+        }
+    }
+}
+
+/**
+Adapts `S::get_star_{STAR}` to `PluginStarFn`, never letting a panic unwind into the caller.
+`cancel` is what `runner::is_cancelled` reports while the star runs.
+
+# Safety
+
+`input` must point to `input_len` readable bytes, `cancel` must be null or valid for reads until
+the call returns, and `answer` must be valid for writes.
+ */
+pub unsafe extern "C" fn star_trampoline<S: AOCSolutions, const STAR: usize>(
+    input: *const u8,
+    input_len: usize,
+    cancel: *const AtomicBool,
+    answer: *mut i64,
+) -> c_int {
+    let input = match std::str::from_utf8(unsafe { slice::from_raw_parts(input, input_len) }) {
+        Ok(input) => input,
+        Err(_) => return 1,
+    };
+    let result = panic::catch_unwind(AssertUnwindSafe(|| unsafe {
+        runner::with_foreign_flag(cancel, || if STAR == 1 { S::get_star_1(input) } else { S::get_star_2(input) })
+    }));
+    match result {
+        Ok(Ok(v)) => {
+            unsafe { *answer = v };
+            0
+        },
+        _ => 1,
+    }
+}
+
+/**
+Exports the given `AOCSolutions` implementations from a `cdylib` under `PLUGIN_ENTRY_SYMBOL`.
+ */
+#[macro_export]
+macro_rules! export_plugin {
+    ($($day:literal => $sol:ty),+ $(,)?) => {
+        #[no_mangle]
+        pub unsafe extern "C" fn aoc_plugin_entries(count: *mut usize) -> *const $crate::plugins::PluginEntry {
+            static ENTRIES: &[$crate::plugins::PluginEntry] = &[
+                $($crate::plugins::PluginEntry::new::<$sol>($day)),+
+            ];
+            unsafe { *count = ENTRIES.len() };
+            ENTRIES.as_ptr()
+        }
+    };
+}
+
+/**
+Star of a loaded plugin, callable like any built-in star.
+ */
+#[derive(Clone, Copy)]
+pub struct PluginStar(PluginStarFn);
+
+impl PluginStar {
+    /**
+    Calls the star, passing on the cancellation token of the current `runner::run_star` (if any).
+     */
+    #[allow(clippy::result_unit_err)] // Mirrors `AOCSolutions`
+    pub fn call(&self, input: &str) -> Result<i64, ()> {
+        let token = runner::current_token();
+        let cancel = token.as_ref().map_or(ptr::null(), runner::CancellationToken::as_ptr);
+        let mut answer: i64 = 0;
+        match unsafe { (self.0)(input.as_ptr(), input.len(), cancel, &mut answer) } {
+            0 => Ok(answer),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct PluginSolution {
+    pub day: usize,
+    pub star_1: PluginStar,
+    pub star_2: PluginStar,
+    /// Shared object this solution was loaded from.
+    pub origin: PathBuf,
+}
+
+impl PluginSolution {
+    pub fn star(&self, star: usize) -> Option<PluginStar> {
+        match star {
+            1 => Some(self.star_1),
+            2 => Some(self.star_2),
+            _ => None,
+        }
+    }
+}
+
+/**
+Loads every shared object in `dir` as a plugin. A missing `dir` yields no plugins; a file that
+fails to load is reported on `stderr` and skipped so that one broken plugin cannot hide the rest.
+ */
+pub fn load_plugins<P: AsRef<Path>>(dir: P) -> Result<Vec<PluginSolution>, Error> {
+    let dir = dir.as_ref();
+    if !dir.is_dir() { return Ok(Vec::new()); }
+
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == std::env::consts::DLL_EXTENSION))
+        .collect();
+    paths.sort();
+
+    let mut solutions = Vec::new();
+    for path in paths {
+        match load_plugin(&path) {
+            Ok(mut loaded) => solutions.append(&mut loaded),
+            Err(e) => eprintln!("[plugins::load_plugins] Skipping `{}`: {}", path.display(), e),
+        }
+    }
+    Ok(solutions)
+}
+
+/**
+Loads the solutions exported by the shared object at `path`.
+ */
+pub fn load_plugin<P: AsRef<Path>>(path: P) -> Result<Vec<PluginSolution>, Error> {
+    let path = path.as_ref();
+    let entries_fn = dl::open_entries_fn(path)?;
+    unsafe { read_entries(entries_fn, path) }
+}
+
+/**
+# Safety
+
+`entries_fn` must follow the contract of `PluginEntriesFn`.
+ */
+unsafe fn read_entries(entries_fn: PluginEntriesFn, origin: &Path) -> Result<Vec<PluginSolution>, Error> {
+    let mut count: usize = 0;
+    let entries = unsafe { entries_fn(&mut count) };
+    if entries.is_null() {
+        return Err(Error::new(ErrorKind::InvalidData, "[plugins::read_entries] Null entry table"));
+    }
+
+    if count > 0 {
+        // Only the leading `abi_version` is known to be where we expect it until it has been checked
+        let abi_version = unsafe { entries.cast::<u32>().read() };
+        if abi_version != PLUGIN_ABI_VERSION {
+            return Err(Error::new(ErrorKind::InvalidData, format!(
+                "[plugins::read_entries] Unsupported ABI version {} (expected {})", abi_version, PLUGIN_ABI_VERSION
+            )));
+        }
+    }
+
+    let entries = unsafe { slice::from_raw_parts(entries, count) };
+
+    Ok(entries.iter()
+        .map(|e| PluginSolution {
+            day: e.day as usize,
+            star_1: PluginStar(e.star_1),
+            star_2: PluginStar(e.star_2),
+            origin: origin.to_path_buf(),
+        })
+        .collect())
+}
+
+#[cfg(unix)]
+mod dl {
+    use std::ffi::{c_char, c_int, c_void, CStr};
+    use std::io::{Error, ErrorKind};
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    use super::{CString, PluginEntriesFn, PLUGIN_ENTRY_SYMBOL};
+
+    const RTLD_NOW: c_int = 2;
+
+    extern "C" {
+        fn dlopen(filename: *const c_char, flag: c_int) -> *mut c_void;
+        fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+        fn dlerror() -> *mut c_char;
+    }
+
+    fn last_error(context: &str) -> Error {
+        let msg = unsafe {
+            let err = dlerror();
+            if err.is_null() { "unknown error".to_string() } else { CStr::from_ptr(err).to_string_lossy().into_owned() }
+        };
+        Error::other(format!("[plugins::dl] {}: {}", context, msg))
+    }
+
+    /**
+    Opens the library at `path` (never closed) and looks up its `PluginEntriesFn`.
+     */
+    pub(super) fn open_entries_fn(path: &Path) -> Result<PluginEntriesFn, Error> {
+        let c_path = CString::new(path.as_os_str().as_bytes())
+            .map_err(|_| Error::new(ErrorKind::InvalidInput, "[plugins::dl] Path contains NUL"))?;
+        let c_symbol = CString::new(PLUGIN_ENTRY_SYMBOL).unwrap();
+
+        let handle = unsafe { dlopen(c_path.as_ptr(), RTLD_NOW) };
+        if handle.is_null() { return Err(last_error("Cannot open library")); }
+        let symbol = unsafe { dlsym(handle, c_symbol.as_ptr()) };
+        if symbol.is_null() { return Err(last_error("Cannot find entry point")); }
+        Ok(unsafe { std::mem::transmute::<*mut c_void, PluginEntriesFn>(symbol) })
+    }
+}
+
+#[cfg(not(unix))]
+mod dl {
+    use std::io::{Error, ErrorKind};
+    use std::path::Path;
+
+    use super::PluginEntriesFn;
+
+    pub(super) fn open_entries_fn(_: &Path) -> Result<PluginEntriesFn, Error> {
+        Err(Error::new(ErrorKind::Unsupported, "[plugins::dl] Plugins are only supported on Unix"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_solutions::day1::Day1;
+
+    struct Panicking;
+
+    /// Polls for cancellation, like a long-running solver should.
+    struct Polling;
+
+    impl AOCSolutions for Polling {
+        fn get_star_1(_: &str) -> Result<i64, ()> {
+            while !runner::is_cancelled() {
+                std::thread::sleep(std::time::Duration::from_millis(1));
+            }
+            Err(())
+        }
+        fn get_star_2(_: &str) -> Result<i64, ()> { Ok(runner::is_cancelled() as i64) }
+    }
+
+    impl AOCSolutions for Panicking {
+        fn get_star_1(_: &str) -> Result<i64, ()> { panic!("[plugins::tests::Panicking] Expected panic") }
+        fn get_star_2(_: &str) -> Result<i64, ()> { Err(()) }
+    }
+
+    crate::export_plugin!(1 => Day1, 99 => Panicking, 100 => Polling);
+
+    #[test]
+    fn test_read_entries() {
+        let solutions = unsafe { read_entries(aoc_plugin_entries, Path::new("test")) }.unwrap();
+        assert_eq!(solutions.iter().map(|s| s.day).collect::<Vec<_>>(), vec![1, 99, 100]);
+        assert_eq!(solutions[0].star_1.call("1\n2\n\n4").unwrap(), 4);
+        assert_eq!(solutions[0].star(2).unwrap().call("1\n2\n\n4\n\n5").unwrap(), 12);
+        assert!(solutions[1].star_1.call("").is_err());
+        assert!(solutions[1].star_2.call("").is_err());
+    }
+
+    /// Entry table of a hypothetical future ABI, smaller than a `PluginEntry`.
+    unsafe extern "C" fn future_abi_entries(count: *mut usize) -> *const PluginEntry {
+        static ENTRIES: [u32; 2] = [PLUGIN_ABI_VERSION + 1, 16];
+        unsafe { *count = 1 };
+        ENTRIES.as_ptr().cast()
+    }
+
+    unsafe extern "C" fn no_entries(count: *mut usize) -> *const PluginEntry {
+        unsafe { *count = 0 };
+        std::ptr::NonNull::dangling().as_ptr()
+    }
+
+    #[test]
+    fn test_read_entries_checks_abi_version_first() {
+        let error = unsafe { read_entries(future_abi_entries, Path::new("test")) }.err().unwrap();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert!(unsafe { read_entries(no_entries, Path::new("test")) }.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_cancellation_reaches_plugin() {
+        let solutions = unsafe { read_entries(aoc_plugin_entries, Path::new("test")) }.unwrap();
+        let polling = solutions[2].star_1;
+        let budget = std::time::Duration::from_millis(50);
+        let bgn = std::time::Instant::now();
+        let result = runner::run_star(move |input| polling.call(input), String::new(), budget);
+        assert_eq!(result.unwrap_err(), runner::RunError::TimedOut(budget));
+        // Returned after noticing the flag, not after the runner's grace period
+        assert!(bgn.elapsed() < budget * 8);
+
+        // Outside of `run_star` there is no flag to pass, which is never cancelled
+        assert_eq!(solutions[2].star_2.call(""), Ok(0));
+        let cancelled = AtomicBool::new(true);
+        let mut answer = -1;
+        assert_eq!(unsafe { (solutions[2].star_2.0)("".as_ptr(), 0, &cancelled, &mut answer) }, 0);
+        assert_eq!(answer, 1);
+        assert!(!runner::is_cancelled());
+    }
+
+    #[test]
+    fn test_load_invalid_plugin() {
+        assert!(load_plugin("Cargo.toml").is_err());
+        assert!(load_plugins("does/not/exist").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,136 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/**
+Fixed-size pool of worker threads running submitted closures in submission order.
+
+Results are collected through the `JobHandle` returned by `submit`, so completion never has to be
+guessed from a timeout. Dropping the pool lets the workers finish all submitted jobs, then joins
+them.
+ */
+pub struct WorkerPool {
+    job_tx: Option<mpsc::Sender<Job>>,
+    workers: Vec<thread::JoinHandle<()>>,
+}
+
+impl WorkerPool {
+    pub fn new(thread_count: usize) -> WorkerPool {
+        assert!(thread_count > 0, "[pool::WorkerPool::new] Pool needs at least one thread");
+
+        let (job_tx, job_rx) = mpsc::channel::<Job>();
+        let job_rx = Arc::new(Mutex::new(job_rx));
+        let workers = (0..thread_count)
+            .map(|_| {
+                let job_rx = Arc::clone(&job_rx);
+                thread::spawn(move || loop {
+                    // Release the lock before running the job
+                    let job = job_rx.lock().unwrap().recv();
+                    match job {
+                        Ok(job) => job(),
+                        Err(_) => break, // Pool dropped and queue drained
+                    }
+                })
+            })
+            .collect();
+
+        WorkerPool { job_tx: Some(job_tx), workers }
+    }
+
+    pub fn thread_count(&self) -> usize {
+        self.workers.len()
+    }
+
+    /**
+    Queues `job` to run on the next free worker. A panic in `job` is caught, leaving the worker
+    alive, and reported by `JobHandle::join`.
+     */
+    pub fn submit<T, F>(&self, job: F) -> JobHandle<T>
+        where T: Send + 'static, F: FnOnce() -> T + Send + 'static {
+        let (result_tx, result_rx) = mpsc::channel();
+        let job: Job = Box::new(move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(job));
+            let _ = result_tx.send(result.ok()); // Handle may have been dropped
+        });
+        self.job_tx.as_ref().unwrap().send(job)
+            .expect("[pool::WorkerPool::submit] All workers have exited");
+        JobHandle { result_rx }
+    }
+
+    /**
+    Runs `f` on every item on the pool and returns the results in item order, or `None` if any job
+    panicked.
+     */
+    pub fn map<I, T, F>(&self, items: I, f: F) -> Option<Vec<T>>
+        where I: IntoIterator, I::Item: Send + 'static, T: Send + 'static, F: Fn(I::Item) -> T + Send + Sync + 'static {
+        let f = Arc::new(f);
+        let handles: Vec<JobHandle<T>> = items.into_iter()
+            .map(|item| {
+                let f = Arc::clone(&f);
+                self.submit(move || f(item))
+            })
+            .collect();
+        handles.into_iter().map(JobHandle::join).collect()
+    }
+}
+
+impl Drop for WorkerPool {
+    fn drop(&mut self) {
+        drop(self.job_tx.take());
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+/**
+Result of a job submitted to a `WorkerPool`.
+ */
+pub struct JobHandle<T> {
+    result_rx: mpsc::Receiver<Option<T>>,
+}
+
+impl<T> JobHandle<T> {
+    /// Blocks until the job has run. Returns `None` if it panicked.
+    pub fn join(self) -> Option<T> {
+        self.result_rx.recv().ok().flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_submit_and_join() {
+        let pool = WorkerPool::new(3);
+        assert_eq!(pool.thread_count(), 3);
+        let handles: Vec<JobHandle<usize>> = (0..10).map(|n| pool.submit(move || n * n)).collect();
+        assert_eq!(handles.into_iter().map(JobHandle::join).collect::<Vec<_>>(), (0..10).map(|n| Some(n * n)).collect::<Vec<_>>());
+        assert_eq!(pool.map(1..=4, |n: u64| n + 1), Some(vec![2, 3, 4, 5]));
+    }
+
+    #[test]
+    fn test_panicking_job() {
+        let pool = WorkerPool::new(1);
+        assert_eq!(pool.submit(|| -> usize { panic!("[pool::tests] Expected panic") }).join(), None);
+        // The only worker survived
+        assert_eq!(pool.submit(|| 7).join(), Some(7));
+        assert_eq!(pool.map([1, 0], |n: usize| 1 / n), None);
+    }
+
+    #[test]
+    fn test_drop_finishes_queued_jobs() {
+        let done = Arc::new(AtomicUsize::new(0));
+        let pool = WorkerPool::new(2);
+        for _ in 0..20 {
+            let done = Arc::clone(&done);
+            pool.submit(move || done.fetch_add(1, Ordering::Relaxed));
+        }
+        drop(pool);
+        assert_eq!(done.load(Ordering::Relaxed), 20);
+    }
+}
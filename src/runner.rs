@@ -0,0 +1,223 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::alloc_stats::{Measurement, MemoryUsage};
+
+/// Time given to a cancelled solver to notice its token and return before it is abandoned.
+const CANCEL_GRACE: Duration = Duration::from_millis(500);
+
+/**
+Cooperative cancellation flag shared between the runner and the thread executing a solver.
+ */
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Flag behind this token, to hand to code that cannot share the `Arc` (see `plugins`).
+    pub(crate) fn as_ptr(&self) -> *const AtomicBool {
+        Arc::as_ptr(&self.0)
+    }
+}
+
+thread_local! {
+    static CURRENT_TOKEN: RefCell<Option<CancellationToken>> = const { RefCell::new(None) };
+    /// Flag owned by the host when running as a plugin, valid while set, see `with_foreign_flag`.
+    static FOREIGN_FLAG: Cell<*const AtomicBool> = const { Cell::new(ptr::null()) };
+}
+
+/**
+Returns whether the solver running on the current thread has been asked to stop.
+
+Long-running solvers should poll this in their outer loops and bail out with `Err(())` once it
+returns `true`. Always `false` outside of `run_star`, e.g., when a solver is called directly.
+ */
+pub fn is_cancelled() -> bool {
+    CURRENT_TOKEN.with(|t| t.borrow().as_ref().is_some_and(CancellationToken::is_cancelled))
+        || FOREIGN_FLAG.with(|flag| {
+            let flag = flag.get();
+            // SAFETY: `with_foreign_flag` only sets flags that stay valid while they are set
+            !flag.is_null() && unsafe { (*flag).load(Ordering::Relaxed) }
+        })
+}
+
+/// Token of the solver running on the current thread, if it was started by `run_star`.
+pub(crate) fn current_token() -> Option<CancellationToken> {
+    CURRENT_TOKEN.with(|t| t.borrow().clone())
+}
+
+/**
+Runs `f` with `is_cancelled` also reporting `flag`, a cancellation flag owned by another copy of
+this crate (a plugin's `runner` is not the host's). A null `flag` is never cancelled.
+
+# Safety
+
+`flag` must be null or valid for reads until `f` returns.
+ */
+pub(crate) unsafe fn with_foreign_flag<R>(flag: *const AtomicBool, f: impl FnOnce() -> R) -> R {
+    struct Restore(*const AtomicBool);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            FOREIGN_FLAG.with(|flag| flag.set(self.0));
+        }
+    }
+
+    let _restore = Restore(FOREIGN_FLAG.with(|previous| previous.replace(flag)));
+    f()
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RunError {
+    /// Solver returned `Err(())`.
+    Failed,
+    /// Solver exceeded its budget and was cancelled.
+    TimedOut(Duration),
+    /// Solver panicked.
+    Panicked,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RunReport {
+    pub answer: i64,
+    pub elapsed: Duration,
+    /// Allocations made while the solver ran, if `CountingAllocator` is the global allocator.
+    pub memory: Option<MemoryUsage>,
+}
+
+/**
+Wall-clock budgets per `(day, star)`, falling back to `default` for unlisted stars.
+ */
+#[derive(Debug, Clone)]
+pub struct Budget {
+    pub default: Duration,
+    pub overrides: HashMap<(usize, usize), Duration>,
+}
+
+impl Default for Budget {
+    fn default() -> Self {
+        Budget { default: Duration::from_secs(60), overrides: HashMap::new() }
+    }
+}
+
+impl Budget {
+    pub fn get(&self, day: usize, star: usize) -> Duration {
+        *self.overrides.get(&(day, star)).unwrap_or(&self.default)
+    }
+
+    /**
+    Applies a budget specification, either `<secs>` for the default budget or `<day>.<star>=<secs>`
+    for a single star.
+     */
+    pub fn apply(&mut self, spec: &str) -> Result<(), String> {
+        let parse_secs = |s: &str| s.parse::<f64>().ok()
+            .filter(|secs| secs.is_finite() && *secs > 0.0)
+            .map(Duration::from_secs_f64)
+            .ok_or_else(|| format!("[runner::Budget::apply] Invalid duration: \"{}\"", s));
+
+        match spec.split_once('=') {
+            None => self.default = parse_secs(spec)?,
+            Some((target, secs)) => {
+                let target = target.split_once('.')
+                    .and_then(|(day, star)| Some((day.parse().ok()?, star.parse().ok()?)))
+                    .ok_or_else(|| format!("[runner::Budget::apply] Invalid target: \"{}\"", target))?;
+                self.overrides.insert(target, parse_secs(secs)?);
+            },
+        }
+        Ok(())
+    }
+}
+
+/**
+Runs `star_fn` (a built-in `StarFn`, plugin star, ...) on its own thread, cancelling it once
+`budget` has elapsed.
+
+A solver that ignores its token keeps running detached after the grace period, but the caller is
+released with `RunError::TimedOut` either way.
+ */
+pub fn run_star<F>(star_fn: F, input: String, budget: Duration) -> Result<RunReport, RunError>
+    where F: FnOnce(&str) -> Result<i64, ()> + Send + 'static {
+    let token = CancellationToken::new();
+    let (result_tx, result_rx) = mpsc::channel();
+    let solver_token = token.clone();
+    let measurement = Measurement::start();
+    thread::spawn(move || {
+        CURRENT_TOKEN.with(|t| *t.borrow_mut() = Some(solver_token));
+        let bgn = Instant::now();
+        let result = star_fn(&input);
+        let _ = result_tx.send((result, bgn.elapsed()));
+    });
+
+    let outcome = match result_rx.recv_timeout(budget) {
+        Err(mpsc::RecvTimeoutError::Timeout) => {
+            token.cancel();
+            let _ = result_rx.recv_timeout(CANCEL_GRACE);
+            return Err(RunError::TimedOut(budget));
+        },
+        outcome => outcome,
+    };
+
+    match outcome {
+        Ok((Ok(answer), elapsed)) => Ok(RunReport { answer, elapsed, memory: measurement.map(Measurement::finish) }),
+        Ok((Err(()), _)) => Err(RunError::Failed),
+        Err(_) => Err(RunError::Panicked),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static OBSERVED_CANCEL: AtomicBool = AtomicBool::new(false);
+
+    fn polling_star(_: &str) -> Result<i64, ()> {
+        while !is_cancelled() {
+            thread::sleep(Duration::from_millis(1));
+        }
+        OBSERVED_CANCEL.store(true, Ordering::Relaxed);
+        Err(())
+    }
+
+    #[test]
+    fn test_run_star() {
+        let report = run_star(|s| Ok(s.len() as i64), "abc".to_string(), Duration::from_secs(1)).unwrap();
+        assert_eq!(report.answer, 3);
+        assert_eq!(run_star(|_| Err(()), String::new(), Duration::from_secs(1)).unwrap_err(), RunError::Failed);
+        assert_eq!(run_star(|_| panic!("oops"), String::new(), Duration::from_secs(1)).unwrap_err(), RunError::Panicked);
+    }
+
+    #[test]
+    fn test_run_star_cancels_on_timeout() {
+        let budget = Duration::from_millis(50);
+        assert_eq!(run_star(polling_star, String::new(), budget).unwrap_err(), RunError::TimedOut(budget));
+        assert!(OBSERVED_CANCEL.load(Ordering::Relaxed));
+        assert!(!is_cancelled());
+    }
+
+    #[test]
+    fn test_budget() {
+        let mut budget = Budget::default();
+        budget.apply("10").unwrap();
+        budget.apply("11.2=120").unwrap();
+        assert_eq!(budget.get(1, 1), Duration::from_secs(10));
+        assert_eq!(budget.get(11, 2), Duration::from_secs(120));
+        assert!(budget.apply("-1").is_err());
+        assert!(budget.apply("11=5").is_err());
+    }
+}
@@ -0,0 +1,154 @@
+use std::fs::{self, OpenOptions};
+use std::io::{Error, ErrorKind, Write};
+use std::path::Path;
+
+const SOLUTIONS_DIR: &str = "src/get_solutions";
+const INPUTS_DIR: &str = "inputs";
+
+/**
+Generates the skeleton for a new day inside the project rooted at `root`:
+
+- `src/get_solutions/day{n}.rs` containing an `AOCSolutions` implementation and a tests module;
+- `inputs/day{n}/input`, left empty for the puzzle input;
+- `pub mod day{n};` and a `solution!` entry in `src/get_solutions/mod.rs`.
+
+Fails without touching anything if the day already exists or `mod.rs` cannot be wired.
+ */
+pub fn new_day<P: AsRef<Path>>(root: P, day: usize) -> Result<(), Error> {
+    let root = root.as_ref();
+    if day == 0 {
+        return Err(Error::new(ErrorKind::InvalidInput, "[scaffold::new_day] Day must be positive"));
+    }
+
+    let solution_path = root.join(SOLUTIONS_DIR).join(format!("day{}.rs", day));
+    if solution_path.exists() {
+        return Err(Error::new(
+            ErrorKind::AlreadyExists,
+            format!("[scaffold::new_day] `{}` already exists", solution_path.display())
+        ));
+    }
+
+    // Wire up `mod.rs` in memory first so that a malformed `mod.rs` leaves no stray files behind
+    let mod_path = root.join(SOLUTIONS_DIR).join("mod.rs");
+    let mod_src = fs::read_to_string(&mod_path)?;
+    let mod_src = register_day(&mod_src, day)?;
+
+    let input_dir = root.join(INPUTS_DIR).join(format!("day{}", day));
+    fs::create_dir_all(&input_dir)?;
+    OpenOptions::new().create(true).append(true).open(input_dir.join("input"))?;
+
+    let mut solution_file = OpenOptions::new().write(true).create_new(true).open(&solution_path)?;
+    solution_file.write_all(solution_template(day).as_bytes())?;
+    fs::write(&mod_path, mod_src)?;
+    Ok(())
+}
+
+/**
+Returns `mod_src` with `pub mod day{day};` appended after the last `pub mod` declaration and a
+`solution!` entry appended after the last registered solution.
+ */
+fn register_day(mod_src: &str, day: usize) -> Result<String, Error> {
+    let malformed = |what: &str| Error::new(
+        ErrorKind::InvalidData,
+        format!("[scaffold::register_day] Cannot find {} in `mod.rs`", what)
+    );
+
+    let lines: Vec<&str> = mod_src.lines().collect();
+    if lines.iter().any(|l| l.trim_start().starts_with(&format!("solution!({},", day))) {
+        return Err(Error::new(
+            ErrorKind::AlreadyExists,
+            format!("[scaffold::register_day] Day {} is already registered", day)
+        ));
+    }
+    let last_mod = lines.iter().rposition(|l| l.starts_with("pub mod day"))
+        .ok_or_else(|| malformed("`pub mod` declarations"))?;
+    let last_entry = lines.iter().rposition(|l| l.trim_start().starts_with("solution!("))
+        .ok_or_else(|| malformed("`SOLUTIONS` entries"))?;
+
+    let mod_line = format!("pub mod day{};", day);
+    let entry_line = format!("    solution!({}, day{}::Day{}),", day, day, day);
+    let mut result: Vec<&str> = Vec::with_capacity(lines.len() + 2);
+    for (idx, line) in lines.iter().enumerate() {
+        result.push(line);
+        if idx == last_mod { result.push(&mod_line); }
+        if idx == last_entry { result.push(&entry_line); }
+    }
+
+    let mut result = result.join("\n");
+    if mod_src.ends_with('\n') { result.push('\n'); }
+    Ok(result)
+}
+
+fn solution_template(day: usize) -> String {
+    format!(r#"use super::AOCSolutions;
+
+pub struct Day{day};
+
+impl AOCSolutions for Day{day} {{
+    fn get_star_1(input: &str) -> Result<i64, ()> {{
+        eprintln!("[Day{day}::get_star_1] Not yet solved, ignoring input of length {{}}", input.len());
+        Err(())
+    }}
+
+    fn get_star_2(input: &str) -> Result<i64, ()> {{
+        eprintln!("[Day{day}::get_star_2] Not yet solved, ignoring input of length {{}}", input.len());
+        Err(())
+    }}
+}}
+
+#[cfg(test)]
+mod tests {{
+    use super::Day{day};
+    use super::AOCSolutions;
+
+    const SAMPLE_INPUT: &str = r"";
+
+    #[test]
+    #[ignore = "fill in `SAMPLE_INPUT` and the expected answer"]
+    fn test_get_star_1() {{
+        assert_eq!(Day{day}::get_star_1(SAMPLE_INPUT).unwrap(), 0);
+    }}
+
+    #[test]
+    #[ignore = "fill in `SAMPLE_INPUT` and the expected answer"]
+    fn test_get_star_2() {{
+        assert_eq!(Day{day}::get_star_2(SAMPLE_INPUT).unwrap(), 0);
+    }}
+}}
+"#, day = day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::register_day;
+
+    const MOD_SRC: &str = r"pub mod day1;
+pub mod day2;
+
+pub const SOLUTIONS: &[Solution] = &[
+    solution!(1, day1::Day1),
+    solution!(2, day2::Day2),
+];
+";
+
+    #[test]
+    fn test_register_day() {
+        let result = register_day(MOD_SRC, 3).unwrap();
+        assert_eq!(result, r"pub mod day1;
+pub mod day2;
+pub mod day3;
+
+pub const SOLUTIONS: &[Solution] = &[
+    solution!(1, day1::Day1),
+    solution!(2, day2::Day2),
+    solution!(3, day3::Day3),
+];
+");
+    }
+
+    #[test]
+    fn test_register_day_rejects_existing() {
+        assert!(register_day(MOD_SRC, 2).is_err());
+        assert!(register_day("", 3).is_err());
+    }
+}
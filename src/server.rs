@@ -0,0 +1,402 @@
+use std::io::{self, BufRead, BufReader, Error, ErrorKind, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::get_solutions::{Solution, SOLUTIONS};
+use crate::runner::{run_star, RunError, RunReport};
+
+const LINGER_TIMEOUT: Duration = Duration::from_millis(100);
+
+/**
+Limits applied to every connection accepted by `serve`.
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct ServerConfig {
+    /// Maximum accepted `Content-Length`, i.e., puzzle input size in bytes.
+    pub max_body_len: usize,
+    /// Maximum accepted length of the request line plus headers, in bytes.
+    pub max_head_len: usize,
+    /// Wall-clock budget for reading the request, solving and writing the response.
+    pub request_timeout: Duration,
+    /// Maximum number of connections handled at once; further ones wait in the listen backlog.
+    pub max_connections: usize,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            max_body_len: 1 << 20,
+            max_head_len: 8 << 10,
+            request_timeout: Duration::from_secs(30),
+            max_connections: 64,
+        }
+    }
+}
+
+struct Response {
+    status: u16,
+    reason: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn error(status: u16, reason: &'static str, msg: &str) -> Response {
+        Response { status, reason, body: format!("{{\"error\":\"{}\"}}", escape_json(msg)) }
+    }
+}
+
+/**
+Serves solutions over HTTP/1.1 on `listener`, one thread per connection and at most
+`max_connections` at once, until accepting fails.
+
+Only `POST /day/{n}/star/{k}` is routed: the request body is the puzzle input and the response is a
+JSON object `{"day":n,"star":k,"answer":...,"elapsed_us":...}`. Every response closes the connection.
+
+A connection keeps its slot until its solver thread has exited too, so solvers that ignore
+cancellation and keep running after a 504 still count towards `max_connections`.
+ */
+pub fn serve(listener: TcpListener, config: ServerConfig) -> Result<(), Error> {
+    serve_solutions(listener, config, SOLUTIONS)
+}
+
+fn serve_solutions(listener: TcpListener, config: ServerConfig, solutions: &'static [Solution]) -> Result<(), Error> {
+    assert!(config.max_connections > 0, "[server::serve] `max_connections` must be positive");
+    let slots = Arc::new(ConnectionSlots::new(config.max_connections));
+    for stream in listener.incoming() {
+        // Wait for a free slot before accepting more
+        let slot = ConnectionSlots::acquire(&slots);
+        let stream = stream?;
+        thread::spawn(move || {
+            let slot = Arc::new(slot);
+            if let Err(e) = handle_connection(stream, &config, solutions, &slot) {
+                eprintln!("[server::serve] Connection dropped: {}", e);
+            }
+        });
+    }
+    Ok(())
+}
+
+/**
+Counting semaphore bounding the number of connection threads.
+ */
+struct ConnectionSlots {
+    free: Mutex<usize>,
+    freed: Condvar,
+}
+
+impl ConnectionSlots {
+    fn new(count: usize) -> ConnectionSlots {
+        ConnectionSlots { free: Mutex::new(count), freed: Condvar::new() }
+    }
+
+    fn acquire(slots: &Arc<ConnectionSlots>) -> ConnectionSlot {
+        let mut free = slots.freed.wait_while(slots.free.lock().unwrap(), |free| *free == 0).unwrap();
+        *free -= 1;
+        ConnectionSlot(Arc::clone(slots))
+    }
+}
+
+/// Slot held by a connection thread, released on drop.
+struct ConnectionSlot(Arc<ConnectionSlots>);
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        *self.0.free.lock().unwrap() += 1;
+        self.0.freed.notify_one();
+    }
+}
+
+/**
+Reads from a `TcpStream` until a deadline: the read timeout is shrunk to the remaining time before
+every read, so a client trickling bytes cannot extend the connection past it.
+ */
+struct DeadlineReader {
+    stream: TcpStream,
+    deadline: Instant,
+}
+
+impl Read for DeadlineReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(Error::new(ErrorKind::TimedOut, "[server::DeadlineReader] Deadline exceeded"));
+        }
+        self.stream.set_read_timeout(Some(remaining))?;
+        self.stream.read(buf)
+    }
+}
+
+fn handle_connection(
+    stream: TcpStream,
+    config: &ServerConfig,
+    solutions: &[Solution],
+    slot: &Arc<ConnectionSlot>,
+) -> Result<(), Error> {
+    let deadline = Instant::now() + config.request_timeout;
+    stream.set_write_timeout(Some(config.request_timeout))?;
+
+    let mut reader = BufReader::new(DeadlineReader { stream: stream.try_clone()?, deadline });
+    let response = match read_request(&mut reader, config) {
+        Ok((method, path, body)) => route(&method, &path, body, deadline, solutions, slot),
+        Err(response) => response,
+    };
+
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status, response.reason, response.body.len(), response.body
+    )?;
+    stream.flush()?;
+
+    // Drain whatever the client is still sending (e.g., a rejected body), otherwise closing with
+    // unread data resets the connection and the client may never see the response
+    stream.shutdown(Shutdown::Write)?;
+    reader.get_mut().deadline = Instant::now() + LINGER_TIMEOUT;
+    let _ = io::copy(&mut reader.take(config.max_body_len as u64), &mut io::sink());
+    Ok(())
+}
+
+/**
+Reads the request line, headers and body from `reader`, returning `(method, path, body)` or the
+error response to be sent back if the request is malformed or exceeds the configured limits.
+ */
+fn read_request<R: BufRead>(reader: &mut R, config: &ServerConfig) -> Result<(String, String, String), Response> {
+    let mut head_len = 0;
+    let mut read_head_line = |reader: &mut R| -> Result<String, Response> {
+        let mut line = String::new();
+        let limit = (config.max_head_len - head_len + 1) as u64;
+        match reader.take(limit).read_line(&mut line) {
+            Ok(0) => Err(Response::error(400, "Bad Request", "Unexpected end of request")),
+            Ok(read_amnt) if head_len + read_amnt > config.max_head_len => {
+                Err(Response::error(431, "Request Header Fields Too Large", "Request head too large"))
+            },
+            Ok(read_amnt) => {
+                head_len += read_amnt;
+                Ok(line.trim_end().to_string())
+            },
+            Err(_) => Err(Response::error(408, "Request Timeout", "Cannot read request head")),
+        }
+    };
+
+    let request_line = read_head_line(reader)?;
+    let (method, path) = match request_line.split(' ').collect::<Vec<&str>>()[..] {
+        [method, path, version] if version.starts_with("HTTP/1.") => (method.to_string(), path.to_string()),
+        _ => return Err(Response::error(400, "Bad Request", "Malformed request line")),
+    };
+
+    let mut content_len: usize = 0;
+    loop {
+        let header = read_head_line(reader)?;
+        if header.is_empty() { break; }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_len = value.trim().parse()
+                    .map_err(|_| Response::error(400, "Bad Request", "Malformed Content-Length"))?;
+            }
+        } else {
+            return Err(Response::error(400, "Bad Request", "Malformed header"));
+        }
+    }
+
+    if content_len > config.max_body_len {
+        return Err(Response::error(413, "Payload Too Large", &format!(
+            "Body of {} bytes exceeds limit of {} bytes", content_len, config.max_body_len
+        )));
+    }
+    let mut body = vec![0u8; content_len];
+    reader.read_exact(&mut body)
+        .map_err(|_| Response::error(408, "Request Timeout", "Cannot read request body"))?;
+    let body = String::from_utf8(body)
+        .map_err(|_| Response::error(400, "Bad Request", "Body is not valid UTF-8"))?;
+
+    Ok((method, path, body))
+}
+
+/**
+Answers a parsed request. The solver thread holds a reference to `slot` until it exits, see
+`serve`.
+ */
+fn route(method: &str, path: &str, body: String, deadline: Instant, solutions: &[Solution], slot: &Arc<ConnectionSlot>) -> Response {
+    let (day, star) = match path.trim_matches('/').split('/').collect::<Vec<&str>>()[..] {
+        ["day", day, "star", star] => match (day.parse::<usize>(), star.parse::<usize>()) {
+            (Ok(day), Ok(star)) => (day, star),
+            _ => return Response::error(400, "Bad Request", "Day and star must be numeric"),
+        },
+        _ => return Response::error(404, "Not Found", &format!("No route for `{}`", path)),
+    };
+    if method != "POST" {
+        return Response::error(405, "Method Not Allowed", "Only POST is supported");
+    }
+    let solution = match solutions.iter().find(|s| s.day == day) {
+        Some(s) => s,
+        None => return Response::error(404, "Not Found", &format!("Day {} is not solved", day)),
+    };
+    let star_fn = match solution.star(star) {
+        Some(star_fn) => star_fn,
+        None => return Response::error(404, "Not Found", &format!("Star {} does not exist", star)),
+    };
+
+    let slot = Arc::clone(slot);
+    let solver = move |input: &str| {
+        let _slot = slot;
+        star_fn(input)
+    };
+    match run_star(solver, body, deadline.saturating_duration_since(Instant::now())) {
+        Ok(RunReport { answer, elapsed, .. }) => Response {
+            status: 200,
+            reason: "OK",
+            body: format!(
+                "{{\"day\":{},\"star\":{},\"answer\":{},\"elapsed_us\":{}}}",
+                day, star, answer, elapsed.as_micros()
+            ),
+        },
+        Err(RunError::Failed) => Response::error(422, "Unprocessable Entity", "Solver rejected input"),
+        Err(RunError::TimedOut(_)) => Response::error(504, "Gateway Timeout", "Solver timed out"),
+        Err(RunError::Panicked) => Response::error(500, "Internal Server Error", "Solver panicked"),
+    }
+}
+
+fn escape_json(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{serve, serve_solutions, ServerConfig};
+    use crate::get_solutions::Solution;
+    use std::io::{Read, Write};
+    use std::net::{SocketAddr, TcpListener, TcpStream};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    const DAY1_SAMPLE: &str = "1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000";
+
+    fn start_server(config: ServerConfig) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || serve(listener, config));
+        addr
+    }
+
+    fn request(addr: SocketAddr, raw: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(raw.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    fn post(addr: SocketAddr, path: &str, body: &str) -> String {
+        request(addr, &format!("POST {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}", path, body.len(), body))
+    }
+
+    #[test]
+    fn test_solve() {
+        let addr = start_server(ServerConfig::default());
+        let response = post(addr, "/day/1/star/1", DAY1_SAMPLE);
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("{\"day\":1,\"star\":1,\"answer\":24000,\"elapsed_us\":"));
+        let response = post(addr, "/day/1/star/2", DAY1_SAMPLE);
+        assert!(response.contains("\"answer\":45000"));
+    }
+
+    #[test]
+    fn test_routing_errors() {
+        let addr = start_server(ServerConfig::default());
+        assert!(post(addr, "/day/99/star/1", "").starts_with("HTTP/1.1 404"));
+        assert!(post(addr, "/day/1/star/3", "").starts_with("HTTP/1.1 404"));
+        assert!(post(addr, "/day/one/star/1", "").starts_with("HTTP/1.1 400"));
+        assert!(post(addr, "/", "").starts_with("HTTP/1.1 404"));
+        assert!(request(addr, "GET /day/1/star/1 HTTP/1.1\r\n\r\n").starts_with("HTTP/1.1 405"));
+        assert!(request(addr, "garbage\r\n\r\n").starts_with("HTTP/1.1 400"));
+    }
+
+    #[test]
+    fn test_limits() {
+        let addr = start_server(ServerConfig { max_body_len: 16, max_head_len: 128, ..ServerConfig::default() });
+        assert!(post(addr, "/day/1/star/1", DAY1_SAMPLE).starts_with("HTTP/1.1 413"));
+        let long_header = format!("POST /day/1/star/1 HTTP/1.1\r\nX-Padding: {}\r\n\r\n", "a".repeat(256));
+        assert!(request(addr, &long_header).starts_with("HTTP/1.1 431"));
+    }
+
+    #[test]
+    fn test_timeout() {
+        let addr = start_server(ServerConfig { request_timeout: Duration::from_millis(200), ..ServerConfig::default() });
+        // Promises a body that never arrives
+        let response = request(addr, "POST /day/1/star/1 HTTP/1.1\r\nContent-Length: 10\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 408"));
+    }
+
+    #[test]
+    fn test_timeout_spans_whole_request() {
+        let timeout = Duration::from_millis(300);
+        let addr = start_server(ServerConfig { request_timeout: timeout, ..ServerConfig::default() });
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(b"POST /day/1/star/1 HTTP/1.1\r\nContent-Length: 100\r\n\r\n").unwrap();
+        // Each byte arrives well within the timeout, the whole body never does
+        let mut writer = stream.try_clone().unwrap();
+        thread::spawn(move || {
+            for _ in 0..100 {
+                thread::sleep(Duration::from_millis(50));
+                if writer.write_all(b"1").is_err() { break; }
+            }
+        });
+
+        let bgn = Instant::now();
+        let mut response = String::new();
+        let _ = stream.read_to_string(&mut response);
+        assert!(response.starts_with("HTTP/1.1 408"));
+        assert!(bgn.elapsed() < timeout * 4);
+    }
+
+    #[test]
+    fn test_max_connections() {
+        let timeout = Duration::from_millis(300);
+        let addr = start_server(ServerConfig { request_timeout: timeout, max_connections: 1, ..ServerConfig::default() });
+        // Holds the only slot until it times out
+        let mut idle = TcpStream::connect(addr).unwrap();
+        thread::sleep(Duration::from_millis(50));
+
+        let bgn = Instant::now();
+        assert!(post(addr, "/day/1/star/1", DAY1_SAMPLE).contains("\"answer\":24000"));
+        assert!(bgn.elapsed() >= timeout / 2);
+        let mut response = String::new();
+        let _ = idle.read_to_string(&mut response);
+        assert!(response.starts_with("HTTP/1.1 408"));
+    }
+
+    fn stubborn_star(_: &str) -> Result<i64, ()> {
+        // Never polls `is_cancelled`
+        thread::sleep(Duration::from_millis(1500));
+        Ok(0)
+    }
+
+    static STUBBORN: [Solution; 1] = [Solution { day: 1, star_1: stubborn_star, star_2: stubborn_star }];
+
+    #[test]
+    fn test_timed_out_solver_keeps_its_slot() {
+        let timeout = Duration::from_millis(100);
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let config = ServerConfig { request_timeout: timeout, max_connections: 1, ..ServerConfig::default() };
+        thread::spawn(move || serve_solutions(listener, config, &STUBBORN));
+
+        let bgn = Instant::now();
+        assert!(post(addr, "/day/1/star/1", "").starts_with("HTTP/1.1 504"));
+        // Only accepted once the first solver has exited, although its request has been answered
+        assert!(post(addr, "/day/1/star/2", "").starts_with("HTTP/1.1 504"));
+        assert!(bgn.elapsed() >= Duration::from_millis(1500));
+    }
+}
@@ -0,0 +1,52 @@
+//! `CountingAllocator` installed as the global allocator of its own test binary, so that the unit
+//! tests of the library keep running on the default allocator.
+
+use std::hint::black_box;
+use std::sync::Barrier;
+use std::thread;
+
+use advent_of_rust_22::alloc_stats::{CountingAllocator, Measurement};
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const BUF_LEN: usize = 1 << 24;
+
+#[test]
+fn test_measurement() {
+    let measurement = Measurement::start().unwrap();
+    let buf = black_box(vec![1u8; BUF_LEN]);
+    drop(buf);
+    let usage = measurement.finish();
+    assert!(usage.peak_bytes >= BUF_LEN);
+    assert!(usage.total_bytes >= BUF_LEN);
+}
+
+#[test]
+fn test_later_measurement_keeps_earlier_peak() {
+    let outer = Measurement::start().unwrap();
+    drop(black_box(vec![1u8; BUF_LEN]));
+    // Used to reset the peak shared by all measurements
+    let inner = Measurement::start().unwrap();
+    inner.finish();
+    assert!(outer.finish().peak_bytes >= BUF_LEN);
+}
+
+#[test]
+fn test_concurrent_measurements() {
+    let barrier = Barrier::new(4);
+    thread::scope(|s| {
+        let handles: Vec<_> = (0..4)
+            .map(|_| s.spawn(|| {
+                let measurement = Measurement::start().unwrap();
+                drop(black_box(vec![1u8; BUF_LEN]));
+                // Every thread starts measuring before any of them finishes
+                barrier.wait();
+                measurement.finish()
+            }))
+            .collect();
+        for handle in handles {
+            assert!(handle.join().unwrap().peak_bytes >= BUF_LEN);
+        }
+    });
+}